@@ -7,9 +7,6 @@ use cortex_m_rt::entry;
 use rtt_target::{rprintln, rtt_init_print};
 use stm32f4xx_hal::{gpio::GpioExt, i2c::I2c, pac, prelude::*, rcc::RccExt};
 
-const RED: (u8, u8, u8) = (255, 0, 0);
-const GREEN: (u8, u8, u8) = (0, 255, 0);
-
 #[entry]
 fn main() -> ! {
     rtt_init_print!();
@@ -31,10 +28,26 @@ fn main() -> ! {
 
         neokeys
             .set_neopixel_colors(&[
-                if (keys >> 0) & 1 == 0 { GREEN } else { RED },
-                if (keys >> 1) & 1 == 0 { GREEN } else { RED },
-                if (keys >> 2) & 1 == 0 { GREEN } else { RED },
-                if (keys >> 3) & 1 == 0 { GREEN } else { RED },
+                if (keys >> 0) & 1 == 0 {
+                    Color::GREEN
+                } else {
+                    Color::RED
+                },
+                if (keys >> 1) & 1 == 0 {
+                    Color::GREEN
+                } else {
+                    Color::RED
+                },
+                if (keys >> 2) & 1 == 0 {
+                    Color::GREEN
+                } else {
+                    Color::RED
+                },
+                if (keys >> 3) & 1 == 0 {
+                    Color::GREEN
+                } else {
+                    Color::RED
+                },
             ])
             .and_then(|_| neokeys.sync_neopixel())
             .expect("Failed to update neopixels");
@@ -25,9 +25,13 @@ fn main() -> ! {
 
     loop {
         let value = neoslider.slider_value().expect("Failed to read slider");
-        let color = color_wheel((value / 3 & 0xFF) as u8);
+        let color = Color::from(Hsv {
+            h: (value as u32 * 360 / 1024) as u16,
+            s: 255,
+            v: 255,
+        });
         neoslider
-            .set_neopixel_colors(&[color.into(), color.into(), color.into(), color.into()])
+            .set_neopixel_colors(&[color; 4])
             .and_then(|_| neoslider.sync_neopixel())
             .expect("Failed to set neopixel colors");
     }
@@ -42,20 +46,3 @@ fn handle_panic(info: &core::panic::PanicInfo) -> ! {
     }
     loop {}
 }
-
-fn color_wheel(byte: u8) -> Color {
-    match byte {
-        0..=84 => Color(255 - byte * 3, 0, byte * 3),
-        85..=169 => Color(0, (byte - 85) * 3, 255 - (byte - 85) * 3),
-        _ => Color((byte - 170) * 3, 255 - (byte - 170) * 3, 0),
-    }
-}
-
-#[derive(Copy, Clone, Debug)]
-struct Color(pub u8, pub u8, pub u8);
-
-impl From<Color> for (u8, u8, u8) {
-    fn from(value: Color) -> Self {
-        (value.0, value.1, value.2)
-    }
-}
@@ -33,11 +33,14 @@ fn main() -> ! {
     rprintln!("Looping...");
     loop {
         let position = encoder.position().expect("Failed to get position");
-        let c = color_wheel(((position & 0xFF) as u8).wrapping_mul(3));
-        let Color(r, g, b) = c.set_brightness(255);
+        let color = Color::from(Hsv {
+            h: (position.rem_euclid(120) * 3) as u16,
+            s: 255,
+            v: 255,
+        });
 
         encoder
-            .set_neopixel_color(r, g, b)
+            .set_neopixel_color(color)
             .and_then(|_| encoder.sync_neopixel())
             .expect("Failed to set neopixel");
 
@@ -56,23 +59,3 @@ fn handle_panic(info: &core::panic::PanicInfo) -> ! {
     }
     loop {}
 }
-
-fn color_wheel(byte: u8) -> Color {
-    match byte {
-        0..=84 => Color(255 - byte * 3, 0, byte * 3),
-        85..=169 => Color(0, (byte - 85) * 3, 255 - (byte - 85) * 3),
-        _ => Color((byte - 170) * 3, 255 - (byte - 170) * 3, 0),
-    }
-}
-
-struct Color(pub u8, pub u8, pub u8);
-
-impl Color {
-    pub fn set_brightness(self, brightness: u8) -> Self {
-        Self(
-            ((self.0 as u16 * brightness as u16) >> 8) as u8,
-            ((self.1 as u16 * brightness as u16) >> 8) as u8,
-            ((self.2 as u16 * brightness as u16) >> 8) as u8,
-        )
-    }
-}
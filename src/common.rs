@@ -1,19 +1,169 @@
 pub type Reg = [u8; 2];
 
+/// The chips that run the Seesaw firmware, by the ID they report in the
+/// `STATUS_HW_ID` register
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HardwareId {
+    ATTINY806 = 0x84,
+    ATTINY807 = 0x85,
+    ATTINY816 = 0x86,
     ATTINY817 = 0x87,
+    ATTINY1616 = 0x88,
+    ATTINY1617 = 0x89,
     SAMD09 = 0x55,
 }
 
+impl HardwareId {
+    /// The pin capabilities of the chip
+    pub const fn pins(self) -> &'static PinMap {
+        match self {
+            Self::ATTINY806 | Self::ATTINY816 | Self::ATTINY1616 => &ATTINY_X16_PINS,
+            Self::ATTINY807 | Self::ATTINY817 | Self::ATTINY1617 => &ATTINY_8X7_PINS,
+            Self::SAMD09 => &SAMD09_PINS,
+        }
+    }
+
+    /// The ADC reference voltage in millivolts for a board powered from
+    /// `vcc_mv`. SAMD09 boards run the chip from a 3.3V regulator whatever
+    /// the supply, while the ATtiny firmware references VCC directly.
+    pub const fn adc_reference_mv(self, vcc_mv: u16) -> u16 {
+        match self {
+            Self::SAMD09 => 3300,
+            _ => vcc_mv,
+        }
+    }
+
+    /// The size in bytes of the firmware's neopixel buffer, which limits the
+    /// length of the strip it can drive: 60 RGB pixels on the smaller ATtinys,
+    /// 250 on the ATtiny16xx and 170 on the SAMD09
+    pub const fn max_neopixel_bytes(self) -> u16 {
+        match self {
+            Self::ATTINY806 | Self::ATTINY807 | Self::ATTINY816 | Self::ATTINY817 => 180,
+            Self::ATTINY1616 | Self::ATTINY1617 => 750,
+            Self::SAMD09 => 510,
+        }
+    }
+}
+
 impl From<HardwareId> for u8 {
     fn from(value: HardwareId) -> Self {
         value as u8
     }
 }
 
+impl TryFrom<u8> for HardwareId {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x84 => Ok(Self::ATTINY806),
+            0x85 => Ok(Self::ATTINY807),
+            0x86 => Ok(Self::ATTINY816),
+            0x87 => Ok(Self::ATTINY817),
+            0x88 => Ok(Self::ATTINY1616),
+            0x89 => Ok(Self::ATTINY1617),
+            0x55 => Ok(Self::SAMD09),
+            _ => Err(value),
+        }
+    }
+}
+
+/// The pins of a Seesaw chip and the modules they can be used with, by Seesaw
+/// pin number.
+///
+/// The SAMD09 firmware addresses ADC, PWM and touch channels by their index in
+/// these lists, whereas the ATtiny firmware addresses them by pin number.
+#[derive(Debug)]
+pub struct PinMap {
+    pub gpio: &'static [u8],
+    pub adc: &'static [u8],
+    pub pwm: &'static [u8],
+    /// Capacitive touch inputs. Only the SAMD09's are known, from the pin map
+    /// in Adafruit's CircuitPython seesaw library, so none are listed for the
+    /// ATtinys.
+    pub touch: &'static [u8],
+    /// NeoPixel output is bit-banged by the firmware, so any GPIO pin can be
+    /// used
+    pub neopixel: &'static [u8],
+    channels_by_pin: bool,
+}
+
+impl PinMap {
+    pub fn is_gpio(&self, pin: u8) -> bool {
+        self.gpio.contains(&pin)
+    }
+
+    /// A bitmask of the GPIO pins, in the layout used by the bulk GPIO
+    /// registers
+    pub fn gpio_mask(&self) -> u32 {
+        self.gpio.iter().fold(0, |mask, pin| mask | 1 << pin)
+    }
+
+    pub fn supports_neopixel(&self, pin: u8) -> bool {
+        self.neopixel.contains(&pin)
+    }
+
+    pub fn adc_channel(&self, pin: u8) -> Option<u8> {
+        self.channel(self.adc, pin)
+    }
+
+    pub fn pwm_channel(&self, pin: u8) -> Option<u8> {
+        self.channel(self.pwm, pin)
+    }
+
+    pub fn touch_channel(&self, pin: u8) -> Option<u8> {
+        self.channel(self.touch, pin)
+    }
+
+    fn channel(&self, pins: &[u8], pin: u8) -> Option<u8> {
+        let index = pins.iter().position(|&p| p == pin)?;
+        Some(if self.channels_by_pin {
+            pin
+        } else {
+            index as u8
+        })
+    }
+}
+
+/// ATtiny806, ATtiny816 and ATtiny1616 (20-pin)
+const ATTINY_X16_GPIO: &[u8] = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+const ATTINY_X16_PINS: PinMap = PinMap {
+    gpio: ATTINY_X16_GPIO,
+    adc: &[0, 1, 2, 3, 4, 5, 14, 15, 16],
+    pwm: &[0, 1, 7, 11, 16],
+    touch: &[],
+    neopixel: ATTINY_X16_GPIO,
+    channels_by_pin: true,
+};
+
+/// ATtiny807, ATtiny817 and ATtiny1617 (24-pin)
+const ATTINY_8X7_GPIO: &[u8] = &[
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+];
+const ATTINY_8X7_PINS: PinMap = PinMap {
+    gpio: ATTINY_8X7_GPIO,
+    adc: &[0, 1, 2, 3, 6, 7, 18, 19, 20],
+    pwm: &[0, 1, 9, 12, 13],
+    touch: &[],
+    neopixel: ATTINY_8X7_GPIO,
+    channels_by_pin: true,
+};
+
+/// SAMD09: GPIO on PA02-PA11, PA14, PA15, PA24 and PA25, ADC on PA02-PA05,
+/// PWM on PA04-PA07, touch on PA07
+const SAMD09_GPIO: &[u8] = &[2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 14, 15, 24, 25];
+const SAMD09_PINS: PinMap = PinMap {
+    gpio: SAMD09_GPIO,
+    adc: &[2, 3, 4, 5],
+    pwm: &[4, 5, 6, 7],
+    touch: &[7],
+    neopixel: SAMD09_GPIO,
+    channels_by_pin: false,
+};
+
+/// The Seesaw firmware modules, by their base register address
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum Modules {
+pub enum Modules {
     Status = 0x00,
     Gpio = 0x01,
     Sercom0 = 0x02,
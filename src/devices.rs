@@ -5,15 +5,70 @@ use crate::{
         encoder::EncoderModule,
         gpio::{GpioModule, PinMode},
         neopixel::NeopixelModule,
-        status::StatusModule,
+        status::{DeviceCapabilities, ProductDateCode, StatusModule},
         timer::TimerModule,
     },
-    seesaw_device, HardwareId, SeesawDeviceInit,
+    seesaw_device, HardwareId, SeesawDevice, SeesawDeviceInit, SeesawError,
 };
 
 /// All devices implement the status module
 impl<D: Driver, T: super::SeesawDevice<Driver = D>> StatusModule<D> for T {}
 
+/// The Seesaw devices known to this library, as identified by the product ID
+/// they report in their `STATUS_VERSION` register
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeviceKind {
+    ArcadeButton1x4,
+    NeoKey1x4,
+    NeoSlider,
+    QuadRotaryEncoder,
+    RotaryEncoder,
+}
+
+impl DeviceKind {
+    pub const ALL: [DeviceKind; 5] = [
+        Self::ArcadeButton1x4,
+        Self::NeoKey1x4,
+        Self::NeoSlider,
+        Self::QuadRotaryEncoder,
+        Self::RotaryEncoder,
+    ];
+
+    pub fn from_product_id(id: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.product_id() == id)
+    }
+
+    pub const fn default_addr(self) -> u8 {
+        match self {
+            Self::ArcadeButton1x4 => ArcadeButton1x4::default_addr(),
+            Self::NeoKey1x4 => NeoKey1x4::default_addr(),
+            Self::NeoSlider => NeoSlider::default_addr(),
+            Self::QuadRotaryEncoder => QuadRotaryEncoder::default_addr(),
+            Self::RotaryEncoder => RotaryEncoder::default_addr(),
+        }
+    }
+
+    pub const fn hardware_id(self) -> HardwareId {
+        match self {
+            Self::ArcadeButton1x4 => ArcadeButton1x4::hardware_id(),
+            Self::NeoKey1x4 => NeoKey1x4::hardware_id(),
+            Self::NeoSlider => NeoSlider::hardware_id(),
+            Self::QuadRotaryEncoder => QuadRotaryEncoder::hardware_id(),
+            Self::RotaryEncoder => RotaryEncoder::hardware_id(),
+        }
+    }
+
+    pub const fn product_id(self) -> u16 {
+        match self {
+            Self::ArcadeButton1x4 => ArcadeButton1x4::product_id(),
+            Self::NeoKey1x4 => NeoKey1x4::product_id(),
+            Self::NeoSlider => NeoSlider::product_id(),
+            Self::QuadRotaryEncoder => QuadRotaryEncoder::product_id(),
+            Self::RotaryEncoder => RotaryEncoder::product_id(),
+        }
+    }
+}
+
 seesaw_device! {
     #[doc(hidden)]
     name: GenericDevice,
@@ -57,10 +112,8 @@ seesaw_device! {
 }
 
 impl<D: Driver> SeesawDeviceInit<D> for ArcadeButton1x4<D> {
-    fn init(mut self) -> Result<Self, Self::Error> {
-        self.reset_and_verify_seesaw()
-            .and_then(|_| self.enable_buttons())
-            .map(|_| self)
+    fn setup(&mut self) -> Result<(), Self::Error> {
+        self.enable_buttons()
     }
 }
 
@@ -97,16 +150,14 @@ seesaw_device! {
     default_addr: 0x30,
     modules: [
         GpioModule,
-        NeopixelModule { num_leds: 4, pin: 3 },
+        NeopixelModule { num_leds: 4, pin: 3, format: Grb },
     ]
 }
 
 impl<D: Driver> SeesawDeviceInit<D> for NeoKey1x4<D> {
-    fn init(mut self) -> Result<Self, Self::Error> {
-        self.reset_and_verify_seesaw()
-            .and_then(|_| self.enable_neopixel())
+    fn setup(&mut self) -> Result<(), Self::Error> {
+        self.enable_neopixel()
             .and_then(|_| self.enable_button_pins())
-            .map(|_| self)
     }
 }
 
@@ -132,15 +183,13 @@ seesaw_device!(
     modules: [
         AdcModule,
         GpioModule,
-        NeopixelModule { num_leds: 4, pin: 14},
+        NeopixelModule { num_leds: 4, pin: 14, format: Grb },
     ]
 );
 
 impl<D: Driver> SeesawDeviceInit<D> for NeoSlider<D> {
-    fn init(mut self) -> Result<Self, Self::Error> {
-        self.reset_and_verify_seesaw()
-            .and_then(|_| self.enable_neopixel())
-            .map(|_| self)
+    fn setup(&mut self) -> Result<(), Self::Error> {
+        self.enable_neopixel()
     }
 }
 
@@ -159,15 +208,181 @@ seesaw_device! {
     modules:  [
         EncoderModule { button_pin: 24 },
         GpioModule,
-        NeopixelModule { num_leds: 1, pin: 6 },
+        NeopixelModule { num_leds: 1, pin: 6, format: Grb },
     ]
 }
 
 impl<D: Driver> SeesawDeviceInit<D> for RotaryEncoder<D> {
-    fn init(mut self) -> Result<Self, Self::Error> {
-        self.reset_and_verify_seesaw()
-            .and_then(|_| self.enable_button())
-            .and_then(|_| self.enable_neopixel())
-            .map(|_| self)
+    fn setup(&mut self) -> Result<(), Self::Error> {
+        self.enable_button().and_then(|_| self.enable_neopixel())
     }
 }
+
+seesaw_device! {
+    /// QuadRotaryEncoder
+    ///
+    /// Encoder | Button Pin
+    /// ---|---
+    /// 0 | 12
+    /// 1 | 14
+    /// 2 | 17
+    /// 3 | 9
+    name: QuadRotaryEncoder,
+    hardware_id: HardwareId::ATTINY817,
+    product_id: 5752,
+    default_addr: 0x49,
+    modules:  [
+        EncoderModule { button_pins: [12, 14, 17, 9] },
+        GpioModule,
+        NeopixelModule { num_leds: 4, pin: 18, format: Grb },
+    ]
+}
+
+impl<D: Driver> SeesawDeviceInit<D> for QuadRotaryEncoder<D> {
+    fn setup(&mut self) -> Result<(), Self::Error> {
+        self.enable_button().and_then(|_| self.enable_neopixel())
+    }
+}
+
+/// A Seesaw device whose type is only known at runtime.
+///
+/// `AnySeesawDevice::new` reads the product ID of the device at the given
+/// address and wraps it in the matching device type, falling back to
+/// `Generic` for product IDs this library doesn't know. The functionality
+/// shared by every device is available directly, and the concrete device can
+/// be recovered with the `as_*` methods or `TryFrom`.
+#[derive(Debug)]
+pub enum AnySeesawDevice<D> {
+    ArcadeButton1x4(ArcadeButton1x4<D>),
+    NeoKey1x4(NeoKey1x4<D>),
+    NeoSlider(NeoSlider<D>),
+    QuadRotaryEncoder(QuadRotaryEncoder<D>),
+    RotaryEncoder(RotaryEncoder<D>),
+    Generic(GenericDevice<D>),
+}
+
+macro_rules! dispatch {
+    ($self:ident, $device:ident => $expr:expr) => {
+        match $self {
+            AnySeesawDevice::ArcadeButton1x4($device) => $expr,
+            AnySeesawDevice::NeoKey1x4($device) => $expr,
+            AnySeesawDevice::NeoSlider($device) => $expr,
+            AnySeesawDevice::QuadRotaryEncoder($device) => $expr,
+            AnySeesawDevice::RotaryEncoder($device) => $expr,
+            AnySeesawDevice::Generic($device) => $expr,
+        }
+    };
+}
+
+impl<D: Driver> AnySeesawDevice<D> {
+    pub fn new(addr: u8, driver: D) -> Result<Self, SeesawError<D::I2cError>> {
+        let mut generic = GenericDevice::new(addr, driver);
+        let product_id = generic.product_info()?.id;
+        let GenericDevice(addr, driver, _) = generic;
+
+        Ok(match DeviceKind::from_product_id(product_id) {
+            Some(DeviceKind::ArcadeButton1x4) => {
+                Self::ArcadeButton1x4(ArcadeButton1x4::new(addr, driver))
+            }
+            Some(DeviceKind::NeoKey1x4) => Self::NeoKey1x4(NeoKey1x4::new(addr, driver)),
+            Some(DeviceKind::NeoSlider) => Self::NeoSlider(NeoSlider::new(addr, driver)),
+            Some(DeviceKind::QuadRotaryEncoder) => {
+                Self::QuadRotaryEncoder(QuadRotaryEncoder::new(addr, driver))
+            }
+            Some(DeviceKind::RotaryEncoder) => {
+                Self::RotaryEncoder(RotaryEncoder::new(addr, driver))
+            }
+            None => Self::Generic(GenericDevice::new(addr, driver)),
+        })
+    }
+
+    /// Run the device-specific initialization of the wrapped device
+    pub fn init(self) -> Result<Self, SeesawError<D::I2cError>> {
+        Ok(match self {
+            Self::ArcadeButton1x4(device) => Self::ArcadeButton1x4(device.init()?),
+            Self::NeoKey1x4(device) => Self::NeoKey1x4(device.init()?),
+            Self::NeoSlider(device) => Self::NeoSlider(device.init()?),
+            Self::QuadRotaryEncoder(device) => Self::QuadRotaryEncoder(device.init()?),
+            Self::RotaryEncoder(device) => Self::RotaryEncoder(device.init()?),
+            Self::Generic(device) => Self::Generic(device.init()?),
+        })
+    }
+
+    /// The type of the wrapped device, or `None` for a generic device
+    pub fn kind(&self) -> Option<DeviceKind> {
+        match self {
+            Self::ArcadeButton1x4(_) => Some(DeviceKind::ArcadeButton1x4),
+            Self::NeoKey1x4(_) => Some(DeviceKind::NeoKey1x4),
+            Self::NeoSlider(_) => Some(DeviceKind::NeoSlider),
+            Self::QuadRotaryEncoder(_) => Some(DeviceKind::QuadRotaryEncoder),
+            Self::RotaryEncoder(_) => Some(DeviceKind::RotaryEncoder),
+            Self::Generic(_) => None,
+        }
+    }
+
+    pub fn addr(&self) -> u8 {
+        dispatch!(self, device => device.addr())
+    }
+
+    pub fn capabilities(&mut self) -> Result<DeviceCapabilities, SeesawError<D::I2cError>> {
+        dispatch!(self, device => device.capabilities())
+    }
+
+    pub fn hardware_id(&mut self) -> Result<u8, SeesawError<D::I2cError>> {
+        dispatch!(self, device => StatusModule::hardware_id(device))
+    }
+
+    pub fn product_info(&mut self) -> Result<ProductDateCode, SeesawError<D::I2cError>> {
+        dispatch!(self, device => device.product_info())
+    }
+
+    pub fn reset(&mut self) -> Result<(), SeesawError<D::I2cError>> {
+        dispatch!(self, device => device.reset())
+    }
+
+    pub fn temp(&mut self) -> Result<f32, SeesawError<D::I2cError>> {
+        dispatch!(self, device => device.temp())
+    }
+}
+
+macro_rules! impl_any_device_conversions {
+    ($($variant:ident $as_fn:ident),* $(,)?) => {
+        impl<D> AnySeesawDevice<D> {
+            $(
+                pub fn $as_fn(&mut self) -> Option<&mut $variant<D>> {
+                    match self {
+                        Self::$variant(device) => Some(device),
+                        _ => None,
+                    }
+                }
+            )*
+        }
+
+        $(
+            impl<D> From<$variant<D>> for AnySeesawDevice<D> {
+                fn from(device: $variant<D>) -> Self {
+                    Self::$variant(device)
+                }
+            }
+
+            impl<D> TryFrom<AnySeesawDevice<D>> for $variant<D> {
+                type Error = AnySeesawDevice<D>;
+
+                fn try_from(device: AnySeesawDevice<D>) -> Result<Self, Self::Error> {
+                    match device {
+                        AnySeesawDevice::$variant(device) => Ok(device),
+                        other => Err(other),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_any_device_conversions! {
+    ArcadeButton1x4 as_arcade_button_1x4,
+    NeoKey1x4 as_neokey_1x4,
+    NeoSlider as_neoslider,
+    QuadRotaryEncoder as_quad_rotary_encoder,
+    RotaryEncoder as_rotary_encoder,
+}
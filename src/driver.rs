@@ -51,6 +51,17 @@ pub trait DriverExt {
         &mut self,
         addr: i2c::SevenBitAddress,
         reg: &Reg,
+    ) -> Result<[u8; N], Self::Error> {
+        self.register_read_with_delay(addr, reg, DELAY_TIME)
+    }
+
+    /// Like `register_read`, but waits `delay_us` between selecting the
+    /// register and reading it, for registers that need longer than usual
+    fn register_read_with_delay<const N: usize>(
+        &mut self,
+        addr: i2c::SevenBitAddress,
+        reg: &Reg,
+        delay_us: u32,
     ) -> Result<[u8; N], Self::Error>;
 
     fn register_write<const N: usize>(
@@ -83,14 +94,15 @@ pub trait DriverExt {
 impl<T: Driver> DriverExt for T {
     type Error = T::I2cError;
 
-    fn register_read<const N: usize>(
+    fn register_read_with_delay<const N: usize>(
         &mut self,
         addr: i2c::SevenBitAddress,
         reg: &Reg,
+        delay_us: u32,
     ) -> Result<[u8; N], Self::Error> {
         let mut buffer = [0u8; N];
         self.write(addr, reg)?;
-        self.delay_us(DELAY_TIME);
+        self.delay_us(delay_us);
         self.read(addr, &mut buffer)?;
         Ok(buffer)
     }
@@ -3,21 +3,33 @@
 #![feature(array_try_map, generic_const_exprs)]
 // TODO improve the organization of the exports/visibility
 use embedded_hal::blocking::delay;
+use modules::status::StatusModule;
 pub mod bus;
 mod common;
 pub mod devices;
 mod driver;
 mod macros;
 pub mod modules;
+pub mod scan;
 pub use common::*;
 pub use devices::*;
 pub use driver::*;
+#[cfg(feature = "smart-leds")]
+#[doc(hidden)]
+pub use smart_leds_trait;
 
 pub mod prelude {
     pub use super::{
         devices::*,
         driver::DriverExt,
-        modules::{adc::*, encoder::*, gpio::*, neopixel::*, status::*, timer::*},
+        modules::{
+            adc::*,
+            encoder::{value::EncoderValue, *},
+            gpio::*,
+            neopixel::*,
+            status::*,
+            timer::{servo::Servo, tone::Melody, *},
+        },
         SeesawDevice, SeesawDeviceInit,
     };
 }
@@ -43,6 +55,26 @@ where
     pub fn acquire_driver(&self) -> bus::BusProxy<'_, M> {
         bus::BusProxy { mutex: &self.mutex }
     }
+
+    /// Probe every non-reserved address for Seesaw devices. See `scan::Scan`
+    /// for details, including what the probe writes to other devices.
+    pub fn scan(&self) -> scan::Scan<bus::BusProxy<'_, M>> {
+        scan::Scan::new(self.acquire_driver())
+    }
+
+    /// Probe only the addresses the known devices can be set to for Seesaw
+    /// devices, see `scan::KNOWN_ADDRS`
+    pub fn scan_known_addrs(&self) -> scan::Scan<bus::BusProxy<'_, M>, scan::KnownAddrs> {
+        scan::Scan::known(self.acquire_driver())
+    }
+
+    /// Probe the given addresses for Seesaw devices
+    pub fn scan_addrs<A: IntoIterator<Item = u8>>(
+        &self,
+        addrs: A,
+    ) -> scan::Scan<bus::BusProxy<'_, M>, A::IntoIter> {
+        scan::Scan::with_addrs(self.acquire_driver(), addrs)
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -51,6 +83,28 @@ pub enum SeesawError<E> {
     I2c(E),
     /// Occurs when an invalid hardware ID is read
     InvalidHardwareId(u8),
+    /// Occurs when the product ID read from the device doesn't match the
+    /// product ID of the device type
+    InvalidProductId(u16),
+    /// Occurs when the device's firmware doesn't report a module that the
+    /// device type uses
+    MissingModule(Modules),
+    /// Occurs when a pin doesn't exist on the device's chip or doesn't support
+    /// the requested function
+    InvalidPin(u8),
+    /// Occurs when the firmware on the device's chip doesn't support the
+    /// requested operation
+    Unsupported,
+    /// Occurs when an encoder index is out of range for the device
+    InvalidEncoder(u8),
+    /// Occurs when a neopixel strip of this many pixels doesn't fit in the
+    /// chip's neopixel buffer
+    InvalidStripLength(u16),
+    /// Occurs when a pixel index is beyond the end of a neopixel strip
+    InvalidPixel(u16),
+    /// Occurs when only part of a neopixel strip is uploaded while a power
+    /// budget is set, as the budget can only be applied to whole frames
+    PartialFrame,
 }
 
 pub trait SeesawDevice {
@@ -60,6 +114,9 @@ pub trait SeesawDevice {
     const DEFAULT_ADDR: u8;
     const HARDWARE_ID: HardwareId;
     const PRODUCT_ID: u16;
+    /// The firmware modules used by the device, which are checked against the
+    /// device's capabilities at init
+    const MODULES: &'static [Modules];
 
     fn addr(&self) -> u8;
 
@@ -73,12 +130,35 @@ pub trait SeesawDevice {
 /// At startup, Seesaw devices typically have a unique set of initialization
 /// calls to be made. e.g. for a Neokey1x4, we're need to enable the on-board
 /// neopixel and also do some pin mode setting to get everything working.
-/// All devices implement `DeviceInit` with a set of sensible defaults. You can
-/// override the default initialization function with your own by calling
-/// `Seesaw::connect_with` instead of `Seesaw::connect`.
-pub trait SeesawDeviceInit<D: Driver>: SeesawDevice<Driver = D>
+/// All devices implement `DeviceInit` with a set of sensible defaults in
+/// `setup`, which `init` runs after resetting the device and verifying its
+/// hardware and product IDs, and checking that its firmware has every module
+/// the device type uses.
+///
+/// If the device runs custom Seesaw firmware that reports a different product
+/// ID, use `init_custom_firmware`, which only verifies the hardware ID.
+pub trait SeesawDeviceInit<D: Driver>:
+    SeesawDevice<Driver = D, Error = SeesawError<D::I2cError>>
 where
     Self: Sized,
 {
-    fn init(self) -> Result<Self, Self::Error>;
+    fn init(mut self) -> Result<Self, Self::Error> {
+        self.reset_and_verify_seesaw()
+            .and_then(|_| self.verify_modules())
+            .and_then(|_| self.setup())
+            .map(|_| self)
+    }
+
+    fn init_custom_firmware(mut self) -> Result<Self, Self::Error> {
+        self.reset_and_verify_hardware()
+            .and_then(|_| self.verify_modules())
+            .and_then(|_| self.setup())
+            .map(|_| self)
+    }
+
+    /// Device-specific setup that runs once the device has been reset and
+    /// verified
+    fn setup(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
@@ -14,11 +14,12 @@ macro_rules! seesaw_device {
         ]
          $(,)?
     ) => {
-        $(#[$attr])*
-        ///
-        #[doc=core::concat!("[Adafruit Product Page](https://www.adafruit.com/product/", core::stringify!($product_id),")")]
-        #[derive(Debug)]
-        pub struct $name<D>(u8, D);
+        device_struct! {
+            $(#[$attr])*
+            ///
+            #[doc=core::concat!("[Adafruit Product Page](https://www.adafruit.com/product/", core::stringify!($product_id),")")]
+            $name, [$($module_name)*]
+        }
 
         impl $name<()> {
             pub const fn default_addr() -> u8 {
@@ -38,6 +39,7 @@ macro_rules! seesaw_device {
             const DEFAULT_ADDR: u8 = $default_addr;
             const HARDWARE_ID: $crate::HardwareId = $hardware_id;
             const PRODUCT_ID: u16 = $product_id;
+            const MODULES: &'static [$crate::Modules] = &[$(device_module_id!($module_name)),*];
 
             fn addr(&self) -> u8 {
                 self.0
@@ -48,11 +50,11 @@ macro_rules! seesaw_device {
             }
 
             fn new(addr: u8, driver: D) -> Self {
-                Self(addr, driver)
+                Self(addr, driver, Default::default())
             }
 
             fn new_with_default_addr(driver: D) -> Self {
-                Self(Self::DEFAULT_ADDR, driver)
+                Self::new(Self::DEFAULT_ADDR, driver)
             }
         }
 
@@ -62,6 +64,49 @@ macro_rules! seesaw_device {
     };
 }
 
+/// Declares a device struct, holding the address, the driver and, for devices
+/// with the `NeopixelModule`, the neopixel settings
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! device_struct {
+    ($(#[$attr:meta])* $name:ident, []) => {
+        $(#[$attr])*
+        #[derive(Debug)]
+        pub struct $name<D>(u8, D, ());
+    };
+    ($(#[$attr:meta])* $name:ident, [NeopixelModule $($rest:ident)*]) => {
+        $(#[$attr])*
+        #[derive(Debug)]
+        pub struct $name<D>(u8, D, $crate::modules::neopixel::NeopixelSettings);
+    };
+    ($(#[$attr:meta])* $name:ident, [$module:ident $($rest:ident)*]) => {
+        device_struct! { $(#[$attr])* $name, [$($rest)*] }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! device_module_id {
+    (AdcModule) => {
+        $crate::Modules::Adc
+    };
+    (EncoderModule) => {
+        $crate::Modules::Encoder
+    };
+    (GpioModule) => {
+        $crate::Modules::Gpio
+    };
+    (NeopixelModule) => {
+        $crate::Modules::Neopixel
+    };
+    (StatusModule) => {
+        $crate::Modules::Status
+    };
+    (TimerModule) => {
+        $crate::Modules::Timer
+    };
+}
+
 #[doc(hidden)]
 #[macro_export(local_inner_macros)]
 macro_rules! impl_device_module {
@@ -70,7 +115,12 @@ macro_rules! impl_device_module {
     };
     ($device:ident, EncoderModule { button_pin: $button_pin:expr }) => {
         impl<D: $crate::Driver> $crate::modules::encoder::EncoderModule<D> for $device<D> {
-            const ENCODER_BTN_PIN: u8 = $button_pin;
+            const ENCODER_BTN_PINS: &'static [u8] = &[$button_pin];
+        }
+    };
+    ($device:ident, EncoderModule { button_pins: $button_pins:expr }) => {
+        impl<D: $crate::Driver> $crate::modules::encoder::EncoderModule<D> for $device<D> {
+            const ENCODER_BTN_PINS: &'static [u8] = &$button_pins;
         }
     };
     ($device:ident, GpioModule $({})?) => {
@@ -80,7 +130,42 @@ macro_rules! impl_device_module {
         impl<D: $crate::Driver> $crate::modules::neopixel::NeopixelModule<D> for $device<D> {
             const N_LEDS: u16 = $num_leds;
             const PIN: u8 = $pin;
+
+            fn neopixel_settings(&self) -> &$crate::modules::neopixel::NeopixelSettings {
+                &self.2
+            }
+
+            fn neopixel_settings_mut(
+                &mut self,
+            ) -> &mut $crate::modules::neopixel::NeopixelSettings {
+                &mut self.2
+            }
+        }
+
+        impl_smart_leds_write! { $device, $num_leds }
+    };
+    ($device:ident, NeopixelModule { num_leds: $num_leds:expr, pin: $pin:expr, format: $format:expr }) => {
+        impl<D: $crate::Driver> $crate::modules::neopixel::NeopixelModule<D> for $device<D> {
+            const N_LEDS: u16 = $num_leds;
+            const PIN: u8 = $pin;
+            const PIXEL_FORMAT: $crate::modules::neopixel::PixelFormat = {
+                #[allow(unused_imports)]
+                use $crate::modules::neopixel::PixelFormat::*;
+                $format
+            };
+
+            fn neopixel_settings(&self) -> &$crate::modules::neopixel::NeopixelSettings {
+                &self.2
+            }
+
+            fn neopixel_settings_mut(
+                &mut self,
+            ) -> &mut $crate::modules::neopixel::NeopixelSettings {
+                &mut self.2
+            }
         }
+
+        impl_smart_leds_write! { $device, $num_leds }
     };
     ($device:ident, StatusModule $({})?) => {
         impl<D: $crate::Driver> $crate::modules::StatusModule<D> for $device<D> {}
@@ -89,3 +174,42 @@ macro_rules! impl_device_module {
         impl<D: $crate::Driver> $crate::modules::timer::TimerModule<D> for $device<D> {}
     };
 }
+
+/// Implements `SmartLedsWrite` for a device with the `NeopixelModule`, when the
+/// `smart-leds` feature is enabled. The feature is checked here rather than in
+/// `seesaw_device!` so that it's the feature of this crate that counts, not
+/// that of the crate declaring the device.
+#[cfg(feature = "smart-leds")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_smart_leds_write {
+    ($device:ident, $num_leds:expr) => {
+        impl<D: $crate::Driver> $crate::smart_leds_trait::SmartLedsWrite for $device<D> {
+            type Color = $crate::modules::neopixel::Color;
+            type Error = $crate::SeesawError<D::I2cError>;
+
+            fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+            where
+                T: IntoIterator<Item = I>,
+                I: Into<Self::Color>,
+            {
+                // Buffered so the whole frame can be measured against the
+                // power budget
+                let mut frame = [$crate::modules::neopixel::Color::BLACK; $num_leds as usize];
+                let mut len = 0;
+                for (pixel, color) in frame.iter_mut().zip(iterator) {
+                    *pixel = color.into();
+                    len += 1;
+                }
+                $crate::modules::neopixel::write_and_sync(self, &frame[..len])
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "smart-leds"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_smart_leds_write {
+    ($device:ident, $num_leds:expr) => {};
+}
@@ -3,21 +3,24 @@ use crate::{
     DriverExt, HardwareId,
 };
 
+pub mod filter;
+
 /// RO - 8 bits
-#[allow(dead_code)]
+/// B0: Error
+/// B1: Window monitor interrupt, set when a conversion falls outside the
+/// window. Reading this register clears it.
 const STATUS: &Reg = &[Modules::Adc.into_u8(), 0x00];
 
 /// WO - 8 bits
 /// Writing a 1 to any bit in this register enables the corresponding interrupt.
 /// Writing zeros to this register has no effect.
-#[allow(dead_code)]
 const INTENSET: &Reg = &[Modules::Adc.into_u8(), 0x02];
 
 /// NOT SUPPORTED BY SEESAW PLATFORM
 ///
 /// WO - 8 bits
-/// Writing a 1 to any bit in this register enables the corresponding interrupt.
-/// Writing zeros to this register has no effect.
+/// Writing a 1 to any bit in this register disables the corresponding
+/// interrupt. Writing zeros to this register has no effect.
 #[allow(dead_code)]
 const INTENCLR: &Reg = &[Modules::Adc.into_u8(), 0x03];
 
@@ -37,6 +40,15 @@ const WINMODE: &Reg = &[Modules::Adc.into_u8(), 0x04];
 #[allow(dead_code)]
 const WINTHRESH: &Reg = &[Modules::Adc.into_u8(), 0x05];
 
+/// Time between selecting a channel register and reading it
+const CHANNEL_SELECT_DELAY_US: u32 = 500;
+
+/// Time between sequential reads on different channels
+const CHANNEL_SPACING_US: u32 = 1_000;
+
+/// Window monitor bit of the `STATUS`, `INTENSET` and `INTENCLR` registers
+const WINMON: u8 = 1 << 1;
+
 /// RO - 16bits
 /// ADC value for channel 0
 const CHANNEL_0: &Reg = &[Modules::Adc.into_u8(), 0x07];
@@ -55,22 +67,142 @@ const CHANNEL_0: &Reg = &[Modules::Adc.into_u8(), 0x07];
 ///
 /// Allow a delay of at least 1ms in between sequential ADC reads on different
 /// channels.
+///
+/// The register map also describes a window monitor, which would set the window
+/// status and assert the interrupt line when a conversion falls outside low and
+/// high thresholds. The Seesaw platform doesn't support configuring the window
+/// or clearing the interrupt, so those methods always return
+/// `SeesawError::Unsupported`.
 pub trait AdcModule<D: crate::Driver>: crate::SeesawDevice<Driver = D> {
     fn analog_read(&mut self, pin: u8) -> Result<u16, crate::SeesawError<D::I2cError>> {
-        let pin_offset = match Self::HARDWARE_ID {
-            HardwareId::ATTINY817 => pin,
-            HardwareId::SAMD09 => match pin {
-                2 => 0,
-                3 => 1,
-                4 => 2,
-                5 => 3,
-                _ => 0,
-            },
-        };
+        let channel = adc_channel(Self::HARDWARE_ID, pin)?;
+        read_channel(self, channel)
+    }
+
+    /// Read several pins, leaving the required time between reads on
+    /// different channels. All pins are validated before any are read.
+    fn analog_read_many<const N: usize>(
+        &mut self,
+        pins: &[u8; N],
+    ) -> Result<[u16; N], crate::SeesawError<D::I2cError>> {
+        let channels = pins.try_map(|pin| adc_channel(Self::HARDWARE_ID, pin))?;
+        let mut values = [0; N];
+
+        for (i, &channel) in channels.iter().enumerate() {
+            if i > 0 {
+                self.driver().delay_us(CHANNEL_SPACING_US);
+            }
+            values[i] = read_channel(self, channel)?;
+        }
+        Ok(values)
+    }
+
+    /// Read a pin `samples` times and return the mean
+    fn analog_read_average(
+        &mut self,
+        pin: u8,
+        samples: u8,
+    ) -> Result<u16, crate::SeesawError<D::I2cError>> {
+        let samples = samples.max(1);
+        let mut sum = 0u32;
+        for _ in 0..samples {
+            sum += self.analog_read(pin)? as u32;
+        }
+        Ok(((sum + samples as u32 / 2) / samples as u32) as u16)
+    }
+
+    /// Read a pin `N` times and return the median, which rejects the
+    /// occasional spike that an average would be pulled by
+    fn analog_read_median<const N: usize>(
+        &mut self,
+        pin: u8,
+    ) -> Result<u16, crate::SeesawError<D::I2cError>> {
+        let () = NonEmpty::<N>::CHECK;
+        let mut samples = [0; N];
+        for sample in samples.iter_mut() {
+            *sample = self.analog_read(pin)?;
+        }
+        samples.sort_unstable();
+        Ok(samples[N / 2])
+    }
+
+    /// Read a pin and convert the reading to millivolts, for a board powered
+    /// from `vcc_mv` (see `HardwareId::adc_reference_mv`)
+    fn analog_read_millivolts(
+        &mut self,
+        pin: u8,
+        vcc_mv: u16,
+    ) -> Result<u16, crate::SeesawError<D::I2cError>> {
+        let reference_mv = Self::HARDWARE_ID.adc_reference_mv(vcc_mv);
+        self.analog_read(pin)
+            .map(|counts| filter::counts_to_millivolts(counts, reference_mv))
+    }
 
+    /// Enable window mode with the given thresholds. Not supported by the
+    /// Seesaw platform.
+    fn enable_adc_window(
+        &mut self,
+        _low: u16,
+        _high: u16,
+    ) -> Result<(), crate::SeesawError<D::I2cError>> {
+        Err(crate::SeesawError::Unsupported)
+    }
+
+    /// Not supported by the Seesaw platform
+    fn disable_adc_window(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+        Err(crate::SeesawError::Unsupported)
+    }
+
+    /// Assert the interrupt line when a conversion falls outside the window
+    fn enable_adc_interrupt(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+        let addr = self.addr();
+        self.driver()
+            .write_u8(addr, INTENSET, WINMON)
+            .map_err(crate::SeesawError::I2c)
+    }
+
+    /// Not supported by the Seesaw platform
+    fn disable_adc_interrupt(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+        Err(crate::SeesawError::Unsupported)
+    }
+
+    /// Whether a conversion has fallen outside the window since the status was
+    /// last read. Reading the status clears it.
+    fn adc_window_status(&mut self) -> Result<bool, crate::SeesawError<D::I2cError>> {
         let addr = self.addr();
         self.driver()
-            .read_u16(addr, &[CHANNEL_0[0], CHANNEL_0[1] + pin_offset])
+            .read_u8(addr, STATUS)
+            .map(|status| status & WINMON != 0)
             .map_err(crate::SeesawError::I2c)
     }
 }
+
+fn adc_channel<E>(hardware_id: HardwareId, pin: u8) -> Result<u8, crate::SeesawError<E>> {
+    hardware_id
+        .pins()
+        .adc_channel(pin)
+        .ok_or(crate::SeesawError::InvalidPin(pin))
+}
+
+fn read_channel<D: crate::Driver, T: AdcModule<D> + ?Sized>(
+    device: &mut T,
+    channel: u8,
+) -> Result<u16, crate::SeesawError<D::I2cError>> {
+    let addr = device.addr();
+    device
+        .driver()
+        .register_read_with_delay(
+            addr,
+            &[CHANNEL_0[0], CHANNEL_0[1] + channel],
+            CHANNEL_SELECT_DELAY_US,
+        )
+        .map(u16::from_be_bytes)
+        .map_err(crate::SeesawError::I2c)
+}
+
+/// Fails to compile when used with no samples
+struct NonEmpty<const N: usize>;
+
+impl<const N: usize> NonEmpty<N> {
+    const CHECK: () = assert!(N > 0, "at least one sample is needed");
+}
@@ -0,0 +1,63 @@
+use super::AdcModule;
+use crate::{driver::Driver, SeesawError};
+
+/// Full scale of the 10-bit ADC
+const ADC_MAX: u32 = 1023;
+
+/// Convert a raw 10-bit ADC reading to millivolts for the given reference
+/// voltage
+pub const fn counts_to_millivolts(counts: u16, reference_mv: u16) -> u16 {
+    ((counts as u32 * reference_mv as u32 + ADC_MAX / 2) / ADC_MAX) as u16
+}
+
+/// Exponential moving average of ADC readings, in integer math.
+///
+/// Each new sample moves the average `1 / 2^shift` of the way towards it, so a
+/// larger `shift` gives a smoother but slower response. The first sample is
+/// taken as-is.
+#[derive(Clone, Copy, Debug)]
+pub struct Ema {
+    /// The average, scaled up by `2^shift` to keep the fractional part
+    scaled: Option<u32>,
+    shift: u8,
+}
+
+impl Ema {
+    pub const fn new(shift: u8) -> Self {
+        assert!(shift < 16);
+        Self {
+            scaled: None,
+            shift,
+        }
+    }
+
+    /// Feed a sample in and return the updated average
+    pub fn update(&mut self, sample: u16) -> u16 {
+        let sample = (sample as u32) << self.shift;
+        let scaled = match self.scaled {
+            None => sample,
+            Some(scaled) => scaled - (scaled >> self.shift) + (sample >> self.shift),
+        };
+        self.scaled = Some(scaled);
+        self.value().unwrap_or_default()
+    }
+
+    /// Read a pin and feed the reading in
+    pub fn read<D: Driver, T: AdcModule<D>>(
+        &mut self,
+        device: &mut T,
+        pin: u8,
+    ) -> Result<u16, SeesawError<D::I2cError>> {
+        device.analog_read(pin).map(|sample| self.update(sample))
+    }
+
+    /// The current average, or `None` if no samples have been fed in yet
+    pub fn value(&self) -> Option<u16> {
+        self.scaled
+            .map(|scaled| ((scaled + (1 << self.shift >> 1)) >> self.shift) as u16)
+    }
+
+    pub fn reset(&mut self) {
+        self.scaled = None;
+    }
+}
@@ -4,6 +4,9 @@ use crate::{
     DriverExt,
 };
 
+pub mod irq;
+pub mod value;
+
 #[allow(dead_code)]
 const STATUS: &Reg = &[Modules::Encoder.into_u8(), 0x00];
 const INT_SET: &Reg = &[Modules::Encoder.into_u8(), 0x10];
@@ -11,50 +14,112 @@ const INT_CLR: &Reg = &[Modules::Encoder.into_u8(), 0x20];
 const POSITION: &Reg = &[Modules::Encoder.into_u8(), 0x30];
 const DELTA: &Reg = &[Modules::Encoder.into_u8(), 0x40];
 
+/// Devices with several encoders address each one by adding its index to the
+/// function byte of the register, e.g. the position of the third encoder is
+/// read from `POSITION + 2`.
+///
+/// The methods without an `encoder` argument act on the first encoder.
 pub trait EncoderModule<D: crate::Driver>: GpioModule<D> {
-    const ENCODER_BTN_PIN: u8;
+    /// The button pin of each encoder, by encoder index
+    const ENCODER_BTN_PINS: &'static [u8];
 
+    /// Set the button pins of all encoders to input pullup
     fn enable_button(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
-        self.set_pin_mode(Self::ENCODER_BTN_PIN, PinMode::InputPullup)
+        let pins = Self::ENCODER_BTN_PINS
+            .iter()
+            .fold(0, |pins, pin| pins | 1 << pin);
+        self.set_pin_mode_bulk(pins, PinMode::InputPullup)
             .map(|_| self.driver().delay_us(125))
     }
 
     fn button(&mut self) -> Result<bool, crate::SeesawError<D::I2cError>> {
-        self.digital_read(Self::ENCODER_BTN_PIN)
+        self.encoder_button(0)
     }
 
     fn delta(&mut self) -> Result<i32, crate::SeesawError<D::I2cError>> {
+        self.encoder_delta(0)
+    }
+
+    fn disable_interrupt(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+        self.disable_encoder_interrupt(0)
+    }
+
+    fn enable_interrupt(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+        self.enable_encoder_interrupt(0)
+    }
+
+    fn position(&mut self) -> Result<i32, crate::SeesawError<D::I2cError>> {
+        self.encoder_position(0)
+    }
+
+    fn set_position(&mut self, pos: i32) -> Result<(), crate::SeesawError<D::I2cError>> {
+        self.set_encoder_position(0, pos)
+    }
+
+    fn encoder_button(&mut self, encoder: u8) -> Result<bool, crate::SeesawError<D::I2cError>> {
+        let pin = Self::ENCODER_BTN_PINS
+            .get(encoder as usize)
+            .ok_or(crate::SeesawError::InvalidEncoder(encoder))?;
+        self.digital_read(*pin)
+    }
+
+    fn encoder_delta(&mut self, encoder: u8) -> Result<i32, crate::SeesawError<D::I2cError>> {
+        let reg = encoder_reg::<D, Self>(DELTA, encoder)?;
         let addr = self.addr();
         self.driver()
-            .read_i32(addr, DELTA)
+            .read_i32(addr, &reg)
             .map_err(crate::SeesawError::I2c)
     }
 
-    fn disable_interrupt(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+    fn disable_encoder_interrupt(
+        &mut self,
+        encoder: u8,
+    ) -> Result<(), crate::SeesawError<D::I2cError>> {
+        let reg = encoder_reg::<D, Self>(INT_CLR, encoder)?;
         let addr = self.addr();
         self.driver()
-            .write_u8(addr, INT_CLR, 1)
+            .write_u8(addr, &reg, 1)
             .map_err(crate::SeesawError::I2c)
     }
 
-    fn enable_interrupt(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+    fn enable_encoder_interrupt(
+        &mut self,
+        encoder: u8,
+    ) -> Result<(), crate::SeesawError<D::I2cError>> {
+        let reg = encoder_reg::<D, Self>(INT_SET, encoder)?;
         let addr = self.addr();
         self.driver()
-            .write_u8(addr, INT_SET, 1)
+            .write_u8(addr, &reg, 1)
             .map_err(crate::SeesawError::I2c)
     }
 
-    fn position(&mut self) -> Result<i32, crate::SeesawError<D::I2cError>> {
+    fn encoder_position(&mut self, encoder: u8) -> Result<i32, crate::SeesawError<D::I2cError>> {
+        let reg = encoder_reg::<D, Self>(POSITION, encoder)?;
         let addr = self.addr();
         self.driver()
-            .read_i32(addr, POSITION)
+            .read_i32(addr, &reg)
             .map_err(crate::SeesawError::I2c)
     }
 
-    fn set_position(&mut self, pos: i32) -> Result<(), crate::SeesawError<D::I2cError>> {
+    fn set_encoder_position(
+        &mut self,
+        encoder: u8,
+        pos: i32,
+    ) -> Result<(), crate::SeesawError<D::I2cError>> {
+        let reg = encoder_reg::<D, Self>(POSITION, encoder)?;
         let addr = self.addr();
         self.driver()
-            .write_i32(addr, POSITION, pos)
+            .write_i32(addr, &reg, pos)
             .map_err(crate::SeesawError::I2c)
     }
 }
+
+/// The register of a function for the given encoder
+fn encoder_reg<D: crate::Driver, T: EncoderModule<D> + ?Sized>(
+    reg: &Reg,
+    encoder: u8,
+) -> Result<Reg, crate::SeesawError<D::I2cError>> {
+    ((encoder as usize) < T::ENCODER_BTN_PINS.len())
+        .then(|| [reg[0], reg[1] + encoder])
+        .ok_or(crate::SeesawError::InvalidEncoder(encoder))
+}
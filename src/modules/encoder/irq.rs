@@ -0,0 +1,174 @@
+use super::EncoderModule;
+use crate::{driver::Driver, SeesawError};
+use embedded_hal::digital::v2::InputPin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonEvent {
+    Pressed,
+    Released,
+}
+
+/// What changed on an encoder since the last event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncoderEvent {
+    /// How far the encoder was turned, in detents
+    pub delta: i32,
+    pub button: Option<ButtonEvent>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum EncoderIrqError<E, P> {
+    Seesaw(SeesawError<E>),
+    /// Reading the host's IRQ pin failed
+    Pin(P),
+}
+
+impl<E, P> From<SeesawError<E>> for EncoderIrqError<E, P> {
+    fn from(value: SeesawError<E>) -> Self {
+        Self::Seesaw(value)
+    }
+}
+
+/// Reads an encoder only when the Seesaw's INT line, connected to a host
+/// input pin, is asserted.
+///
+/// Creating the reader enables the encoder interrupt and the pin-change
+/// interrupt of the encoder's button, so the line is asserted both when the
+/// encoder is turned and when its button changes. Each `poll` with the line
+/// asserted reads the delta and button state together, which also clears the
+/// interrupt. Clearing reads the GPIO interrupt flags, so pin-change interrupts
+/// on other pins of the device are cleared along with it.
+///
+/// This reads the first encoder of a device. The encoders of a multi-encoder
+/// device share the INT line and the GPIO interrupt flags, so a reader for one
+/// of them would clear the flags the others are waiting on; read them all
+/// together with `MultiEncoderIrq` instead.
+#[derive(Debug)]
+pub struct EncoderIrq<P>(MultiEncoderIrq<P, 1>);
+
+impl<P: InputPin> EncoderIrq<P> {
+    pub fn new<D: Driver, T: EncoderModule<D>>(
+        device: &mut T,
+        irq: P,
+    ) -> Result<Self, SeesawError<D::I2cError>> {
+        MultiEncoderIrq::new(device, irq).map(Self)
+    }
+
+    /// Disable the interrupts and give back the IRQ pin
+    pub fn release<D: Driver, T: EncoderModule<D>>(
+        self,
+        device: &mut T,
+    ) -> Result<P, SeesawError<D::I2cError>> {
+        self.0.release(device)
+    }
+
+    /// Read the encoder if the INT line (active low) is asserted. Returns
+    /// `None` if it isn't, or if nothing about the encoder changed.
+    pub fn poll<D: Driver, T: EncoderModule<D>>(
+        &mut self,
+        device: &mut T,
+    ) -> Result<Option<EncoderEvent>, EncoderIrqError<D::I2cError, P::Error>> {
+        self.0.poll(device).map(|[event]| event)
+    }
+}
+
+/// The event of each encoder read by a `MultiEncoderIrq`
+pub type EncoderEvents<const N: usize> = [Option<EncoderEvent>; N];
+
+/// Reads the first `N` encoders of a device only when the Seesaw's INT line,
+/// connected to a host input pin, is asserted.
+///
+/// Works like `EncoderIrq`, except that each `poll` reads every encoder before
+/// clearing the GPIO interrupt flags, so that a change on one encoder can't be
+/// cleared while another is being read.
+#[derive(Debug)]
+pub struct MultiEncoderIrq<P, const N: usize> {
+    irq: P,
+    pressed: [bool; N],
+}
+
+impl<P: InputPin, const N: usize> MultiEncoderIrq<P, N> {
+    /// Create a reader for the first `N` encoders of a device. Fails with
+    /// `InvalidEncoder` if the device has fewer.
+    pub fn new<D: Driver, T: EncoderModule<D>>(
+        device: &mut T,
+        irq: P,
+    ) -> Result<Self, SeesawError<D::I2cError>> {
+        if N > T::ENCODER_BTN_PINS.len() {
+            return Err(SeesawError::InvalidEncoder(T::ENCODER_BTN_PINS.len() as u8));
+        }
+
+        for encoder in 0..N {
+            device.enable_encoder_interrupt(encoder as u8)?;
+        }
+        device.enable_pin_interrupts(button_mask::<D, T, N>())?;
+        device.pin_interrupt_flags()?;
+        let pressed = read_buttons::<D, T, N>(device)?;
+
+        Ok(Self { irq, pressed })
+    }
+
+    /// Disable the interrupts and give back the IRQ pin
+    pub fn release<D: Driver, T: EncoderModule<D>>(
+        self,
+        device: &mut T,
+    ) -> Result<P, SeesawError<D::I2cError>> {
+        for encoder in 0..N {
+            device.disable_encoder_interrupt(encoder as u8)?;
+        }
+        device.disable_pin_interrupts(button_mask::<D, T, N>())?;
+        Ok(self.irq)
+    }
+
+    /// Read every encoder if the INT line (active low) is asserted. Each
+    /// encoder's event is `None` if the line isn't asserted, or if nothing
+    /// about that encoder changed.
+    pub fn poll<D: Driver, T: EncoderModule<D>>(
+        &mut self,
+        device: &mut T,
+    ) -> Result<EncoderEvents<N>, EncoderIrqError<D::I2cError, P::Error>> {
+        let mut events = [None; N];
+        if self.irq.is_high().map_err(EncoderIrqError::Pin)? {
+            return Ok(events);
+        }
+
+        let mut deltas = [0; N];
+        for (encoder, delta) in deltas.iter_mut().enumerate() {
+            *delta = device.encoder_delta(encoder as u8)?;
+        }
+        device.pin_interrupt_flags()?;
+
+        let buttons = read_buttons::<D, T, N>(device)?;
+        for (encoder, event) in events.iter_mut().enumerate() {
+            let pressed = buttons[encoder];
+            let button = match (self.pressed[encoder], pressed) {
+                (false, true) => Some(ButtonEvent::Pressed),
+                (true, false) => Some(ButtonEvent::Released),
+                _ => None,
+            };
+            self.pressed[encoder] = pressed;
+
+            let delta = deltas[encoder];
+            *event = (delta != 0 || button.is_some()).then_some(EncoderEvent { delta, button });
+        }
+        Ok(events)
+    }
+}
+
+/// Whether the buttons of the first `N` encoders are pressed (pulled low), as
+/// `encoder_button` reports them, from a single bulk GPIO read
+fn read_buttons<D: Driver, T: EncoderModule<D>, const N: usize>(
+    device: &mut T,
+) -> Result<[bool; N], SeesawError<D::I2cError>> {
+    let pins = device.digital_read_bulk()?;
+    Ok(core::array::from_fn(|encoder| {
+        pins & 1 << T::ENCODER_BTN_PINS[encoder] == 0
+    }))
+}
+
+/// The GPIO bitmask of the buttons of the first `N` encoders
+fn button_mask<D: Driver, T: EncoderModule<D>, const N: usize>() -> u32 {
+    T::ENCODER_BTN_PINS[..N]
+        .iter()
+        .fold(0, |mask, pin| mask | 1 << pin)
+}
@@ -0,0 +1,150 @@
+use super::EncoderModule;
+use crate::{driver::Driver, SeesawError};
+
+/// What an `EncoderValue` does when it's turned past its minimum or maximum
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Bounds {
+    /// Stop at the limit
+    #[default]
+    Clamp,
+    /// Continue from the opposite limit
+    Wrap,
+}
+
+/// Speeds up an `EncoderValue` when the encoder is turned quickly.
+///
+/// Detents are counted over a window of `window_ms`. Once `threshold` detents
+/// have been counted within the window, every further detent moves the value
+/// by `multiplier` steps instead of one.
+#[derive(Clone, Copy, Debug)]
+pub struct Acceleration {
+    pub window_ms: u32,
+    pub threshold: u16,
+    pub multiplier: u16,
+}
+
+impl Default for Acceleration {
+    fn default() -> Self {
+        Self {
+            window_ms: 100,
+            threshold: 4,
+            multiplier: 5,
+        }
+    }
+}
+
+/// A logical value controlled by an encoder, such as a volume level or a menu
+/// index, fed from the encoder's `delta`.
+///
+/// The value moves by `step` for each detent, is kept within `min..=max` by
+/// clamping or wrapping, and can optionally accelerate when the encoder is
+/// turned quickly.
+#[derive(Clone, Copy, Debug)]
+pub struct EncoderValue {
+    value: i32,
+    min: i32,
+    max: i32,
+    step: i32,
+    bounds: Bounds,
+    acceleration: Option<Acceleration>,
+    window_started_ms: u32,
+    window_count: u32,
+}
+
+impl EncoderValue {
+    /// A value in `min..=max` starting at `initial`, which clamps at the
+    /// limits and moves by one per detent
+    pub fn new(initial: i32, min: i32, max: i32) -> Self {
+        assert!(min <= max);
+        Self {
+            value: initial.clamp(min, max),
+            min,
+            max,
+            step: 1,
+            bounds: Bounds::Clamp,
+            acceleration: None,
+            window_started_ms: 0,
+            window_count: 0,
+        }
+    }
+
+    pub fn with_step(self, step: i32) -> Self {
+        Self { step, ..self }
+    }
+
+    pub fn with_bounds(self, bounds: Bounds) -> Self {
+        Self { bounds, ..self }
+    }
+
+    pub fn with_acceleration(self, acceleration: Acceleration) -> Self {
+        Self {
+            acceleration: Some(acceleration),
+            ..self
+        }
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: i32) {
+        self.value = value.clamp(self.min, self.max);
+    }
+
+    /// Apply a delta read from the encoder at `now_ms` and return the new
+    /// value. `now_ms` is only used for acceleration.
+    pub fn update(&mut self, delta: i32, now_ms: u32) -> i32 {
+        if delta != 0 {
+            let steps = delta as i64 * self.multiplier(delta, now_ms) as i64 * self.step as i64;
+            self.value = self.bounded(self.value as i64 + steps);
+        }
+        self.value
+    }
+
+    /// Read the delta of the first encoder on the device and apply it
+    pub fn poll<D: Driver, T: EncoderModule<D>>(
+        &mut self,
+        device: &mut T,
+        now_ms: u32,
+    ) -> Result<i32, SeesawError<D::I2cError>> {
+        self.poll_encoder(device, 0, now_ms)
+    }
+
+    /// Read the delta of an encoder on a multi-encoder device and apply it
+    pub fn poll_encoder<D: Driver, T: EncoderModule<D>>(
+        &mut self,
+        device: &mut T,
+        encoder: u8,
+        now_ms: u32,
+    ) -> Result<i32, SeesawError<D::I2cError>> {
+        device
+            .encoder_delta(encoder)
+            .map(|delta| self.update(delta, now_ms))
+    }
+
+    fn multiplier(&mut self, delta: i32, now_ms: u32) -> u32 {
+        let Some(acceleration) = self.acceleration else {
+            return 1;
+        };
+
+        if now_ms.wrapping_sub(self.window_started_ms) > acceleration.window_ms {
+            self.window_started_ms = now_ms;
+            self.window_count = 0;
+        }
+        self.window_count = self.window_count.saturating_add(delta.unsigned_abs());
+
+        if self.window_count > acceleration.threshold as u32 {
+            acceleration.multiplier as u32
+        } else {
+            1
+        }
+    }
+
+    fn bounded(&self, value: i64) -> i32 {
+        let (min, max) = (self.min as i64, self.max as i64);
+        match self.bounds {
+            Bounds::Clamp => value.clamp(min, max) as i32,
+            Bounds::Wrap => (min + (value - min).rem_euclid(max - min + 1)) as i32,
+        }
+    }
+}
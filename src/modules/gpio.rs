@@ -1,6 +1,6 @@
 use crate::{
     common::{Modules, Reg},
-    DriverExt,
+    DriverExt, HardwareId,
 };
 
 /// WO - 32 bits
@@ -41,20 +41,17 @@ const TOGGLE: &Reg = &[Modules::Gpio.into_u8(), 0x07];
 /// Writing a 1 to any bit in this register enables the interrupt on the
 /// corresponding pin. When the value on this pin changes, the corresponding
 /// bit will be set in the INTFLAG register. Writing 0 has no effect.
-#[allow(dead_code)]
 const INT_ENABLE: &Reg = &[Modules::Gpio.into_u8(), 0x08];
 
 /// WO - 32 bits
 /// Writing a 1 to any bit in this register disables the interrupt on the
 /// corresponding pin. Writing 0 has no effect.
-#[allow(dead_code)]
 const INT_DISABLE: &Reg = &[Modules::Gpio.into_u8(), 0x09];
 
 /// RO - 32 bits
 /// This register hold the status of all GPIO interrupts.
 /// When an interrupt fires, the corresponding bit in this register gets
 /// set. Reading this register clears all interrupts.
-#[allow(dead_code)]
 const INT_FLAG: &Reg = &[Modules::Gpio.into_u8(), 0x0A];
 
 /// WO - 32 bits
@@ -84,6 +81,7 @@ const PULL_DISABLE: &Reg = &[Modules::Gpio.into_u8(), 0x0C];
 /// The module base register address for the GPIO module is 0x01.
 pub trait GpioModule<D: crate::Driver>: crate::SeesawDevice<Driver = D> {
     fn digital_read(&mut self, pin: u8) -> Result<bool, crate::SeesawError<D::I2cError>> {
+        validate_gpio_pin(Self::HARDWARE_ID, pin)?;
         self.digital_read_bulk()
             .map(|pins| !matches!(pins >> pin & 0x1, 1))
     }
@@ -95,11 +93,39 @@ pub trait GpioModule<D: crate::Driver>: crate::SeesawDevice<Driver = D> {
             .map_err(crate::SeesawError::I2c)
     }
 
+    /// Enable pin-change interrupts on the pins set in `pins`. When one of
+    /// them changes, its bit is set in the interrupt flags and the IRQ line is
+    /// asserted.
+    fn enable_pin_interrupts(&mut self, pins: u32) -> Result<(), crate::SeesawError<D::I2cError>> {
+        validate_gpio_pins(Self::HARDWARE_ID, pins)?;
+        let addr = self.addr();
+        self.driver()
+            .write_u32(addr, INT_ENABLE, pins)
+            .map_err(crate::SeesawError::I2c)
+    }
+
+    fn disable_pin_interrupts(&mut self, pins: u32) -> Result<(), crate::SeesawError<D::I2cError>> {
+        validate_gpio_pins(Self::HARDWARE_ID, pins)?;
+        let addr = self.addr();
+        self.driver()
+            .write_u32(addr, INT_DISABLE, pins)
+            .map_err(crate::SeesawError::I2c)
+    }
+
+    /// The pins whose interrupts have fired. Reading the flags clears them.
+    fn pin_interrupt_flags(&mut self) -> Result<u32, crate::SeesawError<D::I2cError>> {
+        let addr = self.addr();
+        self.driver()
+            .read_u32(addr, INT_FLAG)
+            .map_err(crate::SeesawError::I2c)
+    }
+
     fn set_pin_mode(
         &mut self,
         pin: u8,
         mode: PinMode,
     ) -> Result<(), crate::SeesawError<D::I2cError>> {
+        validate_gpio_pin(Self::HARDWARE_ID, pin)?;
         self.set_pin_mode_bulk(1 << pin, mode)
     }
 
@@ -108,6 +134,7 @@ pub trait GpioModule<D: crate::Driver>: crate::SeesawDevice<Driver = D> {
         pins: u32,
         mode: PinMode,
     ) -> Result<(), crate::SeesawError<D::I2cError>> {
+        validate_gpio_pins(Self::HARDWARE_ID, pins)?;
         let addr = self.addr();
         let bus = self.driver();
 
@@ -128,6 +155,24 @@ pub trait GpioModule<D: crate::Driver>: crate::SeesawDevice<Driver = D> {
     }
 }
 
+fn validate_gpio_pin<E>(hardware_id: HardwareId, pin: u8) -> Result<(), crate::SeesawError<E>> {
+    if hardware_id.pins().is_gpio(pin) {
+        Ok(())
+    } else {
+        Err(crate::SeesawError::InvalidPin(pin))
+    }
+}
+
+/// Fails with the lowest pin in `pins` that isn't a GPIO pin of the chip
+fn validate_gpio_pins<E>(hardware_id: HardwareId, pins: u32) -> Result<(), crate::SeesawError<E>> {
+    match pins & !hardware_id.pins().gpio_mask() {
+        0 => Ok(()),
+        invalid => Err(crate::SeesawError::InvalidPin(
+            invalid.trailing_zeros() as u8
+        )),
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(u8)]
 pub enum PinMode {
@@ -1,8 +1,18 @@
 use crate::{
     common::{Modules, Reg},
     driver::Driver,
-    DriverExt, SeesawDevice, SeesawError,
+    DriverExt, HardwareId, SeesawDevice, SeesawError,
 };
+use embedded_hal::blocking::i2c;
+
+pub mod animation;
+mod color;
+mod settings;
+pub mod shadow;
+pub mod strip;
+
+pub use color::*;
+pub use settings::*;
 
 /// WO - 8 bits
 /// This register sets the pin number (PORTA) that is used for the NeoPixel
@@ -25,98 +35,525 @@ const SET_BUF: &Reg = &[Modules::Neopixel.into_u8(), 0x04];
 /// arguments/data after the command.
 const SHOW: &Reg = &[Modules::Neopixel.into_u8(), 0x05];
 
+/// The most data bytes that can follow the start address in a `SET_BUF` write
+const MAX_CHUNK_LEN: usize = 30;
+
 pub trait NeopixelModule<D: Driver>: SeesawDevice<Driver = D> {
     const PIN: u8;
 
     /// The number of neopixels on the device
     const N_LEDS: u16 = 1;
 
+    /// The channel order of the neopixels, which also determines the number of
+    /// bytes per pixel
+    const PIXEL_FORMAT: PixelFormat = PixelFormat::Rgb;
+
+    /// The device's runtime neopixel settings, which `seesaw_device!` stores in
+    /// devices declared with the `NeopixelModule`
+    fn neopixel_settings(&self) -> &NeopixelSettings;
+
+    fn neopixel_settings_mut(&mut self) -> &mut NeopixelSettings;
+
+    fn neopixel_brightness(&self) -> u8 {
+        self.neopixel_settings().brightness()
+    }
+
+    /// Scale all colors set from now on by `brightness / 255`. Colors already
+    /// uploaded to the device aren't changed.
+    fn set_neopixel_brightness(&mut self, brightness: u8) {
+        self.neopixel_settings_mut().set_brightness(brightness)
+    }
+
+    /// Enable or disable gamma correction of all colors set from now on
+    fn set_neopixel_gamma(&mut self, gamma: bool) {
+        self.neopixel_settings_mut().set_gamma(gamma)
+    }
+
+    /// Limit the estimated current drawn by the neopixels, or remove the limit
+    /// with `None`. Whole frames uploaded at once, by `set_neopixel_colors`, a
+    /// `NeopixelShadow`, an `Animator` or `SmartLedsWrite`, are scaled down to
+    /// fit the budget. While a budget is set, `set_nth_neopixel_color` fails
+    /// with `PartialFrame` on devices with more than one pixel, as the rest of
+    /// the frame is unknown.
+    fn set_neopixel_power_budget(&mut self, power_budget: Option<PowerBudget>) {
+        self.neopixel_settings_mut().set_power_budget(power_budget)
+    }
+
     fn enable_neopixel(&mut self) -> Result<(), SeesawError<D::I2cError>> {
         let addr = self.addr();
-
-        self.driver()
-            .write_u8(addr, SET_PIN, Self::PIN)
-            .and_then(|_| {
-                self.driver().delay_us(10_000);
-                self.driver().write_u16(addr, SET_LEN, 3 * Self::N_LEDS)
-            })
-            .map(|_| self.driver().delay_us(10_000))
-            .map_err(SeesawError::I2c)
+        enable(
+            self.driver(),
+            addr,
+            Self::HARDWARE_ID,
+            Self::PIN,
+            Self::N_LEDS,
+            Self::PIXEL_FORMAT,
+        )
     }
 
     fn set_neopixel_speed(&mut self, speed: NeopixelSpeed) -> Result<(), SeesawError<D::I2cError>> {
         let addr = self.addr();
+        set_speed(self.driver(), addr, speed)?;
+        self.neopixel_settings_mut().set_speed(speed);
+        Ok(())
+    }
 
-        self.driver()
-            .write_u8(
-                addr,
-                SET_SPEED,
-                match speed {
-                    NeopixelSpeed::Khz400 => 0,
-                    NeopixelSpeed::Khz800 => 1,
-                },
-            )
-            .map(|_| self.driver().delay_us(10_000))
-            .map_err(SeesawError::I2c)
+    /// Cap the rate at which `try_sync_neopixel` shows frames, or remove the
+    /// cap with `None`
+    fn set_neopixel_max_fps(&mut self, max_fps: Option<u16>) {
+        self.neopixel_settings_mut().set_max_fps(max_fps)
     }
 
-    fn set_neopixel_color(&mut self, r: u8, g: u8, b: u8) -> Result<(), SeesawError<D::I2cError>> {
-        self.set_nth_neopixel_color(0, r, g, b)
+    fn set_neopixel_color(
+        &mut self,
+        color: impl Into<Color>,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        self.set_nth_neopixel_color(0, color)
     }
 
+    /// Set the color of a pixel. The white channel is ignored for RGB pixel
+    /// formats. Fails with `InvalidPixel` if the device has no pixel `n`.
     fn set_nth_neopixel_color(
         &mut self,
         n: u16,
-        r: u8,
-        g: u8,
-        b: u8,
+        color: impl Into<Color>,
     ) -> Result<(), SeesawError<D::I2cError>> {
-        assert!(n < Self::N_LEDS);
-        let [zero, one] = u16::to_be_bytes(3 * n);
+        if n >= Self::N_LEDS {
+            return Err(SeesawError::InvalidPixel(n));
+        }
         let addr = self.addr();
-
-        self.driver()
-            .register_write(addr, SET_BUF, &[zero, one, r, g, b, 0x00])
+        let pixel = [color.into()].into_iter();
+        let settings = frame_settings(
+            self.neopixel_settings(),
+            Self::N_LEDS,
+            Self::PIXEL_FORMAT,
+            n,
+            pixel.clone(),
+        )?;
+        write_pixels(self.driver(), addr, Self::PIXEL_FORMAT, &settings, n, pixel)
             .map_err(SeesawError::I2c)
     }
 
     fn set_neopixel_colors(
         &mut self,
-        colors: &[(u8, u8, u8); Self::N_LEDS as usize],
+        colors: &[impl Into<Color> + Copy; Self::N_LEDS as usize],
     ) -> Result<(), SeesawError<D::I2cError>>
     where
         [(); Self::N_LEDS as usize]: Sized,
     {
         let addr = self.addr();
-
-        (0..Self::N_LEDS)
-            .into_iter()
-            .try_for_each(|n| {
-                let [zero, one] = u16::to_be_bytes(3 * n);
-                let color = colors[n as usize];
-                self.driver().register_write(
-                    addr,
-                    SET_BUF,
-                    &[zero, one, color.0, color.1, color.2, 0x00],
-                )
-            })
-            .map_err(SeesawError::I2c)
+        let colors = colors.iter().map(|&color| color.into());
+        let settings = self
+            .neopixel_settings()
+            .limit(Self::PIXEL_FORMAT, colors.clone());
+        write_pixels(
+            self.driver(),
+            addr,
+            Self::PIXEL_FORMAT,
+            &settings,
+            0,
+            colors,
+        )
+        .map_err(SeesawError::I2c)
     }
 
+    /// Show the uploaded colors, then wait until the pixels have latched them
+    /// so that the next `SHOW` can't corrupt the output. On long strips, use
+    /// `try_sync_neopixel` to avoid the wait.
     fn sync_neopixel(&mut self) -> Result<(), SeesawError<D::I2cError>> {
         let addr = self.addr();
+        let mut settings = *self.neopixel_settings();
+        let result = show(
+            self.driver(),
+            addr,
+            &mut settings,
+            Self::N_LEDS,
+            Self::PIXEL_FORMAT,
+        );
+        *self.neopixel_settings_mut() = settings;
+        result
+    }
 
-        self.driver()
-            .register_write(addr, SHOW, &[])
-            .map(|_| self.driver().delay_us(125))
-            .map_err(SeesawError::I2c)
+    /// Show the uploaded colors unless the last frame shown by this method is
+    /// still being latched, or was shown less than a frame ago at the max
+    /// FPS. Returns whether the colors were shown, without ever waiting.
+    fn try_sync_neopixel(
+        &mut self,
+        clock: &mut impl MonotonicClock,
+    ) -> Result<bool, SeesawError<D::I2cError>> {
+        let addr = self.addr();
+        let mut settings = *self.neopixel_settings();
+        let shown = try_show(
+            self.driver(),
+            addr,
+            &mut settings,
+            Self::N_LEDS,
+            Self::PIXEL_FORMAT,
+            clock,
+        )?;
+        *self.neopixel_settings_mut() = settings;
+        Ok(shown)
     }
 }
 
+/// Point the neopixel output at a pin and size the buffer for a strip of `len`
+/// pixels, after checking that the chip can drive it
+pub(crate) fn enable<D: Driver>(
+    driver: &mut D,
+    addr: u8,
+    hardware_id: HardwareId,
+    pin: u8,
+    len: u16,
+    format: PixelFormat,
+) -> Result<(), SeesawError<D::I2cError>> {
+    if !hardware_id.pins().supports_neopixel(pin) {
+        return Err(SeesawError::InvalidPin(pin));
+    }
+    let buffer_len = format.bytes_per_pixel() as u32 * len as u32;
+    if buffer_len > hardware_id.max_neopixel_bytes() as u32 {
+        return Err(SeesawError::InvalidStripLength(len));
+    }
+
+    driver
+        .write_u8(addr, SET_PIN, pin)
+        .and_then(|_| {
+            driver.delay_us(10_000);
+            driver.write_u16(addr, SET_LEN, buffer_len as u16)
+        })
+        .map(|_| driver.delay_us(10_000))
+        .map_err(SeesawError::I2c)
+}
+
+pub(crate) fn set_speed<D: Driver>(
+    driver: &mut D,
+    addr: u8,
+    speed: NeopixelSpeed,
+) -> Result<(), SeesawError<D::I2cError>> {
+    driver
+        .write_u8(
+            addr,
+            SET_SPEED,
+            match speed {
+                NeopixelSpeed::Khz400 => 0,
+                NeopixelSpeed::Khz800 => 1,
+            },
+        )
+        .map(|_| driver.delay_us(10_000))
+        .map_err(SeesawError::I2c)
+}
+
+/// Send `SHOW` for a strip of `len` pixels and wait until the pixels have
+/// latched it. If a frame shown by `try_show` may still be latching, its show
+/// time is waited out first, as there's no clock to tell how long ago it was
+/// shown.
+pub(crate) fn show<D: Driver>(
+    driver: &mut D,
+    addr: u8,
+    settings: &mut NeopixelSettings,
+    len: u16,
+    format: PixelFormat,
+) -> Result<(), SeesawError<D::I2cError>> {
+    let show_time_us = settings
+        .speed()
+        .show_time_us(len as u32 * format.bytes_per_pixel() as u32);
+    if settings.take_last_show_us().is_some() {
+        driver.delay_us(show_time_us);
+    }
+
+    driver
+        .register_write(addr, SHOW, &[])
+        .map(|_| driver.delay_us(show_time_us))
+        .map_err(SeesawError::I2c)
+}
+
+/// Send `SHOW` for a strip of `len` pixels, if the interval since the last
+/// `SHOW` sent by this function has passed
+pub(crate) fn try_show<D: Driver>(
+    driver: &mut D,
+    addr: u8,
+    settings: &mut NeopixelSettings,
+    len: u16,
+    format: PixelFormat,
+    clock: &mut impl MonotonicClock,
+) -> Result<bool, SeesawError<D::I2cError>> {
+    let now_us = clock.now_us();
+    if !settings.show_due(len, format, now_us) {
+        return Ok(false);
+    }
+
+    driver
+        .register_write(addr, SHOW, &[])
+        .map_err(SeesawError::I2c)?;
+    settings.set_last_show_us(now_us);
+    Ok(true)
+}
+
+/// Upload a frame of pixels from the start of the strip, ignoring any beyond
+/// `N_LEDS`, and show them. Used by the `SmartLedsWrite` implementations.
+#[doc(hidden)]
+pub fn write_and_sync<D: Driver, T: NeopixelModule<D>>(
+    device: &mut T,
+    frame: &[Color],
+) -> Result<(), SeesawError<D::I2cError>> {
+    write_frame(device, frame).and_then(|_| device.sync_neopixel())
+}
+
+/// Upload and show a frame like `write_and_sync`, but only once the last frame
+/// shown with the clock has latched, and without waiting for this one to.
+/// Returns whether the frame was uploaded and shown.
+pub(crate) fn write_and_try_sync<D: Driver, T: NeopixelModule<D>>(
+    device: &mut T,
+    frame: &[Color],
+    clock: &mut impl MonotonicClock,
+) -> Result<bool, SeesawError<D::I2cError>> {
+    let now_us = clock.now_us();
+    if !show_due(device, now_us) {
+        return Ok(false);
+    }
+    write_frame(device, frame)?;
+    device.try_sync_neopixel(&mut || now_us)
+}
+
+/// A device's neopixels driven through `SmartLedsWrite` without blocking, timed
+/// by a `MonotonicClock`. Each `write` uploads and shows its frame only once
+/// the last one has latched, and otherwise drops it.
+#[cfg(feature = "smart-leds")]
+#[derive(Debug)]
+pub struct ClockedNeopixels<D, T: NeopixelModule<D>, C>
+where
+    D: Driver,
+{
+    device: T,
+    clock: C,
+    _driver: core::marker::PhantomData<D>,
+}
+
+#[cfg(feature = "smart-leds")]
+impl<D: Driver, T: NeopixelModule<D>, C: MonotonicClock> ClockedNeopixels<D, T, C> {
+    pub fn new(device: T, clock: C) -> Self {
+        Self {
+            device,
+            clock,
+            _driver: core::marker::PhantomData,
+        }
+    }
+
+    pub fn device(&mut self) -> &mut T {
+        &mut self.device
+    }
+
+    pub fn into_inner(self) -> (T, C) {
+        (self.device, self.clock)
+    }
+}
+
+#[cfg(feature = "smart-leds")]
+impl<D: Driver, T: NeopixelModule<D>, C: MonotonicClock> smart_leds_trait::SmartLedsWrite
+    for ClockedNeopixels<D, T, C>
+where
+    [(); T::N_LEDS as usize]: Sized,
+{
+    type Color = Color;
+    type Error = SeesawError<D::I2cError>;
+
+    fn write<I, P>(&mut self, iterator: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Self::Color>,
+    {
+        let mut frame = [Color::BLACK; T::N_LEDS as usize];
+        let mut len = 0;
+        for (pixel, color) in frame.iter_mut().zip(iterator) {
+            *pixel = color.into();
+            len += 1;
+        }
+        write_and_try_sync(&mut self.device, &frame[..len], &mut self.clock).map(|_| ())
+    }
+}
+
+/// Whether the device's last frame shown with a clock has latched by `now_us`,
+/// so that its buffer can be written and shown again
+pub(crate) fn show_due<D: Driver, T: NeopixelModule<D>>(device: &T, now_us: u32) -> bool {
+    device
+        .neopixel_settings()
+        .show_due(T::N_LEDS, T::PIXEL_FORMAT, now_us)
+}
+
+fn write_frame<D: Driver, T: NeopixelModule<D>>(
+    device: &mut T,
+    frame: &[Color],
+) -> Result<(), SeesawError<D::I2cError>> {
+    let frame = &frame[..frame.len().min(T::N_LEDS as usize)];
+    let addr = device.addr();
+    let settings = frame_settings(
+        device.neopixel_settings(),
+        T::N_LEDS,
+        T::PIXEL_FORMAT,
+        0,
+        frame.iter().copied(),
+    )?;
+    write_pixels(
+        device.driver(),
+        addr,
+        T::PIXEL_FORMAT,
+        &settings,
+        0,
+        frame.iter().copied(),
+    )
+    .map_err(SeesawError::I2c)
+}
+
+/// The settings to upload `pixels` from pixel `start` of a strip of `len`
+/// pixels with. While a power budget is set, only whole frames can be measured
+/// against it, so anything less fails with `PartialFrame`.
+pub(crate) fn frame_settings<E>(
+    settings: &NeopixelSettings,
+    len: u16,
+    format: PixelFormat,
+    start: u16,
+    pixels: impl ExactSizeIterator<Item = Color>,
+) -> Result<NeopixelSettings, SeesawError<E>> {
+    let whole = start == 0 && pixels.len() == len as usize;
+    if !whole && settings.power_budget().is_some() {
+        return Err(SeesawError::PartialFrame);
+    }
+    Ok(settings.limit(format, pixels))
+}
+
+/// Upload contiguous pixels, starting at pixel `start`, to the device's pixel
+/// buffer, after applying the brightness and gamma settings. The encoded pixels
+/// are packed into as few `SET_BUF` writes as possible, splitting pixels across
+/// writes where needed.
+pub(crate) fn write_pixels<D: Driver>(
+    driver: &mut D,
+    addr: u8,
+    format: PixelFormat,
+    settings: &NeopixelSettings,
+    start: u16,
+    pixels: impl Iterator<Item = Color>,
+) -> Result<(), D::I2cError> {
+    let bytes_per_pixel = format.bytes_per_pixel();
+    let mut chunks = ChunkWriter::new(start * bytes_per_pixel);
+
+    for color in pixels.map(|color| settings.correct(color)) {
+        let encoded = format.encode(color);
+        chunks.push(driver, addr, &encoded[..bytes_per_pixel as usize])?;
+    }
+    chunks.flush(driver, addr)
+}
+
+/// Packs a stream of bytes into `SET_BUF` writes of up to `MAX_CHUNK_LEN`
+/// bytes each
+struct ChunkWriter {
+    buffer: [u8; 4 + MAX_CHUNK_LEN],
+    len: usize,
+    offset: u16,
+}
+
+impl ChunkWriter {
+    fn new(offset: u16) -> Self {
+        Self {
+            buffer: [0; 4 + MAX_CHUNK_LEN],
+            len: 0,
+            offset,
+        }
+    }
+
+    fn push<D: Driver>(
+        &mut self,
+        driver: &mut D,
+        addr: u8,
+        bytes: &[u8],
+    ) -> Result<(), D::I2cError> {
+        for &byte in bytes {
+            if self.len == MAX_CHUNK_LEN {
+                self.flush(driver, addr)?;
+            }
+            self.buffer[4 + self.len] = byte;
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    fn flush<D: Driver>(&mut self, driver: &mut D, addr: u8) -> Result<(), D::I2cError> {
+        if self.len == 0 {
+            return Ok(());
+        }
+        self.buffer[0..2].copy_from_slice(SET_BUF);
+        self.buffer[2..4].copy_from_slice(&self.offset.to_be_bytes());
+
+        i2c::Write::write(driver, addr, &self.buffer[..4 + self.len])?;
+        driver.delay_us(125);
+        self.offset += self.len as u16;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+/// How long the pixels take to latch their data once it stops arriving
+const LATCH_US: u32 = 300;
+
 /// NeopixelModule: The Neopixel protocol speed
-#[derive(Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum NeopixelSpeed {
     Khz400 = 0,
     #[default]
     Khz800 = 1,
 }
+
+impl NeopixelSpeed {
+    /// The time from a `SHOW` command until `bytes` bytes of pixel data have
+    /// been sent and latched, before which another `SHOW` corrupts the output
+    pub const fn show_time_us(self, bytes: u32) -> u32 {
+        let bits = bytes * 8;
+        let send_us = match self {
+            Self::Khz400 => bits * 5 / 2,
+            Self::Khz800 => bits * 5 / 4,
+        };
+        send_us + LATCH_US
+    }
+}
+
+/// A monotonic clock for timing `SHOW` commands without blocking, e.g. backed
+/// by a hardware timer
+pub trait MonotonicClock {
+    /// Microseconds since an arbitrary point, wrapping on overflow
+    fn now_us(&mut self) -> u32;
+}
+
+impl<F: FnMut() -> u32> MonotonicClock for F {
+    fn now_us(&mut self) -> u32 {
+        self()
+    }
+}
+
+/// NeopixelModule: The order of the color channels of each pixel, and whether
+/// it has a white channel
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PixelFormat {
+    #[default]
+    Rgb,
+    Grb,
+    Rgbw,
+    Grbw,
+}
+
+impl PixelFormat {
+    pub const fn bytes_per_pixel(self) -> u16 {
+        match self {
+            Self::Rgb | Self::Grb => 3,
+            Self::Rgbw | Self::Grbw => 4,
+        }
+    }
+
+    /// Arrange the channels in the order they're sent to the pixel. Only the
+    /// first `bytes_per_pixel` bytes are used.
+    pub const fn encode(self, Color { r, g, b, w }: Color) -> [u8; 4] {
+        match self {
+            Self::Rgb => [r, g, b, 0],
+            Self::Grb => [g, r, b, 0],
+            Self::Rgbw => [r, g, b, w],
+            Self::Grbw => [g, r, b, w],
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use super::{write_and_sync, write_and_try_sync, Color, Hsv, MonotonicClock, NeopixelModule};
+use crate::{driver::Driver, SeesawError};
+use core::marker::PhantomData;
+
+/// A built-in effect, rendered from the time since it started.
+///
+/// Some effects only light part of the strip and leave the rest transparent,
+/// which lets lower layers of an `Animator` show through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    /// Transparent everywhere
+    None,
+    Solid(Color),
+    /// On for the first half of each period, transparent for the second
+    Blink {
+        color: Color,
+        period_ms: u32,
+    },
+    /// Fades smoothly up from off to full brightness and back once per period
+    Breathe {
+        color: Color,
+        period_ms: u32,
+    },
+    /// A rainbow spread across the strip that cycles once per period
+    Rainbow {
+        period_ms: u32,
+    },
+    /// A head of light running along the strip once per period with a fading
+    /// tail of `tail` pixels behind it
+    Comet {
+        color: Color,
+        period_ms: u32,
+        tail: u16,
+    },
+    /// Random pixels flash and fade out. Every `interval_ms`, each pixel has a
+    /// `density / 255` chance of lighting up.
+    Sparkle {
+        color: Color,
+        interval_ms: u32,
+        density: u8,
+    },
+}
+
+impl Effect {
+    /// The color of pixel `n` of a strip of `len` pixels, `elapsed_ms` after
+    /// the effect started, or `None` if the pixel is transparent
+    pub fn render(&self, n: u16, len: u16, elapsed_ms: u32) -> Option<Color> {
+        match *self {
+            Self::None => None,
+            Self::Solid(color) => Some(color),
+            Self::Blink { color, period_ms } => {
+                (phase(elapsed_ms, period_ms) < 128).then_some(color)
+            }
+            Self::Breathe { color, period_ms } => {
+                let phase = phase(elapsed_ms, period_ms) as u16;
+                let level = if phase < 128 {
+                    phase * 2
+                } else {
+                    (255 - phase) * 2
+                };
+                // Squaring the level makes the fade look more even to the eye
+                Some(color.scale((level * level / 255) as u8))
+            }
+            Self::Rainbow { period_ms } => {
+                let offset = n as u32 * 360 / len.max(1) as u32;
+                let h = (phase(elapsed_ms, period_ms) as u32 * 360 / 256 + offset) % 360;
+                Some(
+                    Hsv {
+                        h: h as u16,
+                        s: 255,
+                        v: 255,
+                    }
+                    .into(),
+                )
+            }
+            Self::Comet {
+                color,
+                period_ms,
+                tail,
+            } => {
+                let travel = len as u32 + tail as u32;
+                let head = phase(elapsed_ms, period_ms) as u32 * travel / 256;
+                let behind = head.checked_sub(n as u32)?;
+                (behind <= tail as u32).then(|| {
+                    let level = 255 - behind * 255 / (tail as u32 + 1);
+                    color.scale(level as u8)
+                })
+            }
+            Self::Sparkle {
+                color,
+                interval_ms,
+                density,
+            } => {
+                let interval_ms = interval_ms.max(1);
+                let slot = elapsed_ms / interval_ms;
+                let lit = (hash(n as u32, slot) & 0xFF) < density as u32;
+                lit.then(|| {
+                    let fade = (elapsed_ms % interval_ms) * 255 / interval_ms;
+                    color.scale(255 - fade as u8)
+                })
+            }
+        }
+    }
+}
+
+/// An effect applied to a range of pixels
+#[derive(Clone, Copy, Debug)]
+struct Ranged {
+    effect: Effect,
+    started_ms: u32,
+    start: u16,
+    len: u16,
+}
+
+impl Ranged {
+    /// The effect's color for pixel `n` of the strip, or `None` if it's
+    /// transparent there or outside the range
+    fn render(&self, n: u16, now_ms: u32) -> Option<Color> {
+        let n = n.checked_sub(self.start).filter(|&n| n < self.len)?;
+        self.effect
+            .render(n, self.len, now_ms.wrapping_sub(self.started_ms))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Layer {
+    current: Ranged,
+    /// The effect being faded into and the fade's duration
+    transition: Option<(Ranged, u32)>,
+}
+
+impl Layer {
+    /// The color of pixel `n` with this layer drawn over `below`, or `None` if
+    /// the layer doesn't cover the pixel
+    fn render(&self, n: u16, now_ms: u32, below: Color) -> Option<Color> {
+        let current = self.current.render(n, now_ms);
+        let Some((next, duration_ms)) = self.transition else {
+            return current;
+        };
+        let elapsed_ms = now_ms.wrapping_sub(next.started_ms);
+        let t = (elapsed_ms.min(duration_ms) * 255 / duration_ms.max(1)) as u8;
+
+        match (current, next.render(n, now_ms)) {
+            (None, None) => None,
+            // A transparent side shows the layers below
+            (current, next) => Some(current.unwrap_or(below).lerp(next.unwrap_or(below), t)),
+        }
+    }
+}
+
+/// Renders layered effects onto a strip of `N` pixels.
+///
+/// Up to `L` layers each run an effect over a range of pixels. Layers are
+/// stacked in index order, so a pixel takes the color of the highest layer that
+/// isn't transparent there, and pixels that no layer lights are off. A layer's
+/// effect can be crossfaded into another over a given duration, with any
+/// transparent pixels of either effect fading to or from the layers below.
+///
+/// Layer indexes of `L` or more are out of range, and calls that set or clear
+/// such a layer are ignored. `N` must be the number of pixels of the device
+/// the frames are uploaded to, which is checked at compile time.
+///
+/// Call `tick` with the current time in milliseconds as often as you like;
+/// frames are only uploaded when the frame interval has passed and the frame
+/// differs from the last one uploaded. `tick` waits for each frame to latch
+/// after showing it, so to never block, call `try_tick` with a
+/// `MonotonicClock` instead.
+#[derive(Debug)]
+pub struct Animator<const N: usize, const L: usize> {
+    layers: [Option<Layer>; L],
+    frame: [Color; N],
+    frame_interval_ms: u32,
+    last_frame_ms: Option<u32>,
+    /// Whether the rendered frame has been uploaded and shown
+    uploaded: bool,
+}
+
+impl<const N: usize, const L: usize> Default for Animator<N, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const L: usize> Animator<N, L> {
+    pub const fn new() -> Self {
+        Self {
+            layers: [None; L],
+            frame: [Color::BLACK; N],
+            frame_interval_ms: 20,
+            last_frame_ms: None,
+            uploaded: false,
+        }
+    }
+
+    /// The minimum time between uploaded frames. Defaults to 20ms (50 FPS).
+    pub const fn with_frame_interval(self, frame_interval_ms: u32) -> Self {
+        Self {
+            frame_interval_ms,
+            ..self
+        }
+    }
+
+    /// Run an effect on every pixel, on the given layer
+    pub fn set_effect(&mut self, layer: usize, effect: Effect, now_ms: u32) {
+        self.set_layer(layer, effect, 0, N as u16, now_ms)
+    }
+
+    /// Run an effect on `len` pixels starting at `start`, on the given layer
+    pub fn set_layer(&mut self, layer: usize, effect: Effect, start: u16, len: u16, now_ms: u32) {
+        let Some(slot) = self.layers.get_mut(layer) else {
+            return;
+        };
+        *slot = Some(Layer {
+            current: Ranged {
+                effect,
+                started_ms: now_ms,
+                start,
+                len,
+            },
+            transition: None,
+        });
+    }
+
+    pub fn clear_layer(&mut self, layer: usize) {
+        if let Some(slot) = self.layers.get_mut(layer) {
+            *slot = None;
+        }
+    }
+
+    /// Fade a layer from its current effect into `effect` over `duration_ms`,
+    /// keeping the layer's range. A layer that isn't set fades in over every
+    /// pixel.
+    pub fn crossfade_to(&mut self, layer: usize, effect: Effect, duration_ms: u32, now_ms: u32) {
+        let (start, len) = match self.layers.get(layer) {
+            None => return,
+            Some(Some(Layer {
+                transition: Some((next, _)),
+                ..
+            })) => (next.start, next.len),
+            Some(Some(Layer { current, .. })) => (current.start, current.len),
+            Some(None) => (0, N as u16),
+        };
+        self.crossfade_layer_to(layer, effect, start, len, duration_ms, now_ms)
+    }
+
+    /// Fade a layer from its current effect into `effect` running on `len`
+    /// pixels starting at `start`, over `duration_ms`. Pixels covered by only
+    /// one of the two effects, and pixels where either is transparent, fade
+    /// between it and the layers below.
+    pub fn crossfade_layer_to(
+        &mut self,
+        layer: usize,
+        effect: Effect,
+        start: u16,
+        len: u16,
+        duration_ms: u32,
+        now_ms: u32,
+    ) {
+        if layer >= L {
+            return;
+        }
+        self.finish_transitions(now_ms);
+        // A crossfade that's still running is replaced, fading from the effect
+        // it started from
+        let current = match self.layers[layer] {
+            Some(layer) => layer.current,
+            None => Ranged {
+                effect: Effect::None,
+                started_ms: now_ms,
+                start,
+                len,
+            },
+        };
+        let next = Ranged {
+            effect,
+            started_ms: now_ms,
+            start,
+            len,
+        };
+        self.layers[layer] = Some(Layer {
+            current,
+            transition: Some((next, duration_ms)),
+        });
+    }
+
+    /// Render the frame for `now_ms` without uploading it
+    pub fn render(&mut self, now_ms: u32) -> &[Color; N] {
+        self.finish_transitions(now_ms);
+        let previous = self.frame;
+        for (n, pixel) in self.frame.iter_mut().enumerate() {
+            *pixel = self
+                .layers
+                .iter()
+                .flatten()
+                .fold(Color::BLACK, |below, layer| {
+                    layer.render(n as u16, now_ms, below).unwrap_or(below)
+                });
+        }
+        if self.frame != previous {
+            self.uploaded = false;
+        }
+        &self.frame
+    }
+
+    /// Render and upload a frame if the frame interval has passed. Returns
+    /// whether a frame was uploaded.
+    pub fn tick<D: Driver, T: NeopixelModule<D>>(
+        &mut self,
+        device: &mut T,
+        now_ms: u32,
+    ) -> Result<bool, SeesawError<D::I2cError>> {
+        let () = FrameLen::<N, D, T>::CHECK;
+        if !self.render_due(now_ms) {
+            return Ok(false);
+        }
+        write_and_sync(device, &self.frame)?;
+        self.uploaded = true;
+        Ok(true)
+    }
+
+    /// Render and upload a frame like `tick`, but only once the last frame
+    /// shown with the clock has latched, and without waiting for this one to.
+    /// If the last frame is still latching, the next call tries again
+    /// regardless of the frame interval.
+    pub fn try_tick<D: Driver, T: NeopixelModule<D>>(
+        &mut self,
+        device: &mut T,
+        now_ms: u32,
+        clock: &mut impl MonotonicClock,
+    ) -> Result<bool, SeesawError<D::I2cError>> {
+        let () = FrameLen::<N, D, T>::CHECK;
+        if !self.render_due(now_ms) {
+            return Ok(false);
+        }
+        self.uploaded = write_and_try_sync(device, &self.frame, clock)?;
+        if !self.uploaded {
+            self.last_frame_ms = None;
+        }
+        Ok(self.uploaded)
+    }
+
+    /// Render a frame if the frame interval has passed, returning whether it
+    /// still has to be uploaded
+    fn render_due(&mut self, now_ms: u32) -> bool {
+        if let Some(last) = self.last_frame_ms {
+            if now_ms.wrapping_sub(last) < self.frame_interval_ms {
+                return false;
+            }
+        }
+        self.last_frame_ms = Some(now_ms);
+        self.render(now_ms);
+        !self.uploaded
+    }
+
+    fn finish_transitions(&mut self, now_ms: u32) {
+        for layer in self.layers.iter_mut().flatten() {
+            if let Some((next, duration_ms)) = layer.transition {
+                if now_ms.wrapping_sub(next.started_ms) >= duration_ms {
+                    layer.current = next;
+                    layer.transition = None;
+                }
+            }
+        }
+    }
+}
+
+/// Fails to compile when the frames of `N` pixels don't fit the device exactly
+struct FrameLen<const N: usize, D, T>(PhantomData<(D, T)>);
+
+impl<const N: usize, D: Driver, T: NeopixelModule<D>> FrameLen<N, D, T> {
+    const CHECK: () = assert!(
+        N == T::N_LEDS as usize,
+        "the animator must have as many pixels as the device"
+    );
+}
+
+/// Position within a period, scaled to 0-255
+fn phase(elapsed_ms: u32, period_ms: u32) -> u8 {
+    let period_ms = period_ms.max(1);
+    ((elapsed_ms % period_ms) as u64 * 256 / period_ms as u64) as u8
+}
+
+/// Cheap deterministic pseudo-random hash, so sparkles need no state
+fn hash(n: u32, slot: u32) -> u32 {
+    let mut x = n.wrapping_mul(0x9E37_79B9) ^ slot.wrapping_mul(0x85EB_CA6B) ^ 0x2545_F491;
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    x
+}
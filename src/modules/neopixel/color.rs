@@ -0,0 +1,230 @@
+use core::str::FromStr;
+
+/// The color of a pixel. The white channel is only sent to RGBW pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub w: u8,
+}
+
+impl Color {
+    pub const BLACK: Self = Self::from_hex(0x000000);
+    pub const BLUE: Self = Self::from_hex(0x0000FF);
+    pub const CYAN: Self = Self::from_hex(0x00FFFF);
+    pub const GREEN: Self = Self::from_hex(0x00FF00);
+    pub const MAGENTA: Self = Self::from_hex(0xFF00FF);
+    pub const ORANGE: Self = Self::from_hex(0xFF8000);
+    pub const PINK: Self = Self::from_hex(0xFF80C0);
+    pub const PURPLE: Self = Self::from_hex(0x8000FF);
+    pub const RED: Self = Self::from_hex(0xFF0000);
+    pub const WHITE: Self = Self::from_hex(0xFFFFFF);
+    pub const YELLOW: Self = Self::from_hex(0xFFFF00);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, w: 0 }
+    }
+
+    pub const fn rgbw(r: u8, g: u8, b: u8, w: u8) -> Self {
+        Self { r, g, b, w }
+    }
+
+    /// A color from its 24-bit `0xRRGGBB` value. The top byte is ignored.
+    pub const fn from_hex(hex: u32) -> Self {
+        Self::rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    /// The 24-bit `0xRRGGBB` value of the color, without its white channel
+    pub const fn to_hex(self) -> u32 {
+        (self.r as u32) << 16 | (self.g as u32) << 8 | self.b as u32
+    }
+
+    /// Scale every channel by `level / 255`
+    pub const fn scale(self, level: u8) -> Self {
+        const fn scale(c: u8, level: u8) -> u8 {
+            ((c as u16 * (level as u16 + 1)) >> 8) as u8
+        }
+        Self::rgbw(
+            scale(self.r, level),
+            scale(self.g, level),
+            scale(self.b, level),
+            scale(self.w, level),
+        )
+    }
+
+    /// Linear interpolation from `self` (t = 0) to `other` (t = 255)
+    pub const fn lerp(self, other: Self, t: u8) -> Self {
+        const fn lerp(a: u8, b: u8, t: u8) -> u8 {
+            ((a as u16 * (255 - t as u16) + b as u16 * t as u16 + 127) / 255) as u8
+        }
+        Self::rgbw(
+            lerp(self.r, other.r, t),
+            lerp(self.g, other.g, t),
+            lerp(self.b, other.b, t),
+            lerp(self.w, other.w, t),
+        )
+    }
+}
+
+/// A color as hue (0-359 degrees), saturation and value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Hsv {
+    pub h: u16,
+    pub s: u8,
+    pub v: u8,
+}
+
+/// A color as hue (0-359 degrees), saturation and lightness
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Hsl {
+    pub h: u16,
+    pub s: u8,
+    pub l: u8,
+}
+
+impl From<Hsv> for Color {
+    fn from(Hsv { h, s, v }: Hsv) -> Self {
+        let (s, v) = (s as u32, v as u32);
+        let h = h as u32 % 360;
+        let chroma = v * s / 255;
+        from_hue(h, chroma, v - chroma)
+    }
+}
+
+impl From<Color> for Hsv {
+    fn from(color: Color) -> Self {
+        let (max, min) = max_min(color);
+        Self {
+            h: hue(color, max, min),
+            s: match max {
+                0 => 0,
+                _ => ((max - min) * 255 / max) as u8,
+            },
+            v: max as u8,
+        }
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(Hsl { h, s, l }: Hsl) -> Self {
+        let (s, l) = (s as u32, l as u32);
+        let h = h as u32 % 360;
+        let chroma = (255 - (2 * l).abs_diff(255)) * s / 255;
+        from_hue(h, chroma, l - chroma / 2)
+    }
+}
+
+impl From<Color> for Hsl {
+    fn from(color: Color) -> Self {
+        let (max, min) = max_min(color);
+        Self {
+            h: hue(color, max, min),
+            s: match max - min {
+                0 => 0,
+                delta => (delta * 255 / (255 - (max + min).abs_diff(255))) as u8,
+            },
+            l: ((max + min) / 2) as u8,
+        }
+    }
+}
+
+/// The RGB color with the given hue, chroma and minimum channel value
+fn from_hue(h: u32, chroma: u32, min: u32) -> Color {
+    // The middle channel rises and falls between the primaries
+    let x = chroma * (60 - (h % 120).abs_diff(60)) / 60;
+    let (r, g, b) = match h / 60 {
+        0 => (chroma, x, 0),
+        1 => (x, chroma, 0),
+        2 => (0, chroma, x),
+        3 => (0, x, chroma),
+        4 => (x, 0, chroma),
+        _ => (chroma, 0, x),
+    };
+    Color::rgb((r + min) as u8, (g + min) as u8, (b + min) as u8)
+}
+
+fn max_min(Color { r, g, b, .. }: Color) -> (u32, u32) {
+    (r.max(g).max(b) as u32, r.min(g).min(b) as u32)
+}
+
+fn hue(Color { r, g, b, .. }: Color, max: u32, min: u32) -> u16 {
+    let delta = (max - min) as i32;
+    if delta == 0 {
+        return 0;
+    }
+    let (r, g, b, max) = (r as i32, g as i32, b as i32, max as i32);
+    let h = if max == r {
+        60 * (g - b) / delta
+    } else if max == g {
+        120 + 60 * (b - r) / delta
+    } else {
+        240 + 60 * (r - g) / delta
+    };
+    h.rem_euclid(360) as u16
+}
+
+/// The error from parsing a `Color` from a string that isn't a 24-bit hex
+/// color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseColorError;
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parse a 24-bit hex color like `#FF8000`, with or without the `#`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if hex.len() != 6 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ParseColorError);
+        }
+        u32::from_str_radix(hex, 16)
+            .map(Self::from_hex)
+            .map_err(|_| ParseColorError)
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+impl From<(u8, u8, u8, u8)> for Color {
+    fn from((r, g, b, w): (u8, u8, u8, u8)) -> Self {
+        Self::rgbw(r, g, b, w)
+    }
+}
+
+impl From<Color> for (u8, u8, u8) {
+    fn from(color: Color) -> Self {
+        (color.r, color.g, color.b)
+    }
+}
+
+impl From<Color> for (u8, u8, u8, u8) {
+    fn from(color: Color) -> Self {
+        (color.r, color.g, color.b, color.w)
+    }
+}
+
+#[cfg(feature = "smart-leds")]
+impl From<smart_leds_trait::RGB8> for Color {
+    fn from(color: smart_leds_trait::RGB8) -> Self {
+        Self::rgb(color.r, color.g, color.b)
+    }
+}
+
+#[cfg(feature = "smart-leds")]
+impl From<smart_leds_trait::RGBW<u8>> for Color {
+    fn from(color: smart_leds_trait::RGBW<u8>) -> Self {
+        Self::rgbw(color.r, color.g, color.b, color.a.0)
+    }
+}
+
+#[cfg(feature = "smart-leds")]
+impl From<Color> for smart_leds_trait::RGB8 {
+    fn from(color: Color) -> Self {
+        Self::new(color.r, color.g, color.b)
+    }
+}
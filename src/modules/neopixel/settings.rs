@@ -0,0 +1,201 @@
+use super::{Color, NeopixelSpeed, PixelFormat};
+
+/// Runtime neopixel settings, kept by every device declared with the
+/// `NeopixelModule` and by every `NeopixelStrip`, and applied whenever colors
+/// are uploaded or shown through them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NeopixelSettings {
+    brightness: u8,
+    gamma: bool,
+    power_budget: Option<PowerBudget>,
+    speed: NeopixelSpeed,
+    max_fps: Option<u16>,
+    last_show_us: Option<u32>,
+}
+
+impl Default for NeopixelSettings {
+    fn default() -> Self {
+        Self {
+            brightness: u8::MAX,
+            gamma: false,
+            power_budget: None,
+            speed: NeopixelSpeed::Khz800,
+            max_fps: None,
+            last_show_us: None,
+        }
+    }
+}
+
+impl NeopixelSettings {
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Scale every channel by `brightness / 255`
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.brightness = brightness;
+    }
+
+    pub fn gamma(&self) -> bool {
+        self.gamma
+    }
+
+    /// Map every channel through `GAMMA8` before scaling it by the brightness,
+    /// so that color values look perceptually linear
+    pub fn set_gamma(&mut self, gamma: bool) {
+        self.gamma = gamma;
+    }
+
+    pub fn power_budget(&self) -> Option<PowerBudget> {
+        self.power_budget
+    }
+
+    /// Limit the estimated current drawn by each frame, or remove the limit
+    /// with `None`. Frames must be uploaded whole for the limit to apply.
+    pub fn set_power_budget(&mut self, power_budget: Option<PowerBudget>) {
+        self.power_budget = power_budget;
+    }
+
+    /// The protocol speed last set on the device
+    pub fn speed(&self) -> NeopixelSpeed {
+        self.speed
+    }
+
+    pub(crate) fn set_speed(&mut self, speed: NeopixelSpeed) {
+        self.speed = speed;
+    }
+
+    pub fn max_fps(&self) -> Option<u16> {
+        self.max_fps
+    }
+
+    /// Cap the rate at which frames are shown by the clocked show methods, or
+    /// remove the cap with `None`
+    pub fn set_max_fps(&mut self, max_fps: Option<u16>) {
+        self.max_fps = max_fps;
+    }
+
+    /// The shortest time between two `SHOW` commands for a strip of `len`
+    /// pixels: the time to send and latch the pixels, or the frame time at the
+    /// max FPS if that's longer
+    pub fn show_interval_us(&self, len: u16, format: PixelFormat) -> u32 {
+        let frame_us = match self.max_fps {
+            Some(fps) => 1_000_000 / fps.max(1) as u32,
+            None => 0,
+        };
+        self.speed
+            .show_time_us(len as u32 * format.bytes_per_pixel() as u32)
+            .max(frame_us)
+    }
+
+    /// Whether a `SHOW` for a strip of `len` pixels can be sent at `now_us`
+    /// without corrupting the frame last shown with a clock
+    pub(crate) fn show_due(&self, len: u16, format: PixelFormat, now_us: u32) -> bool {
+        match self.last_show_us {
+            Some(last_us) => now_us.wrapping_sub(last_us) >= self.show_interval_us(len, format),
+            None => true,
+        }
+    }
+
+    pub(crate) fn set_last_show_us(&mut self, now_us: u32) {
+        self.last_show_us = Some(now_us);
+    }
+
+    /// Forget the time of the last frame shown with a clock, returning it
+    pub(crate) fn take_last_show_us(&mut self) -> Option<u32> {
+        self.last_show_us.take()
+    }
+
+    /// The settings to upload a frame of pixels in the given format with: if
+    /// the frame would draw more than the power budget allows, the brightness
+    /// is scaled down to fit it
+    pub fn limit(&self, format: PixelFormat, frame: impl Iterator<Item = Color>) -> Self {
+        let Some(budget) = self.power_budget else {
+            return *self;
+        };
+        let level = budget.level(format, frame.map(|color| self.correct(color)));
+        Self {
+            brightness: ((self.brightness as u16 * (level as u16 + 1)) >> 8) as u8,
+            ..*self
+        }
+    }
+
+    /// Apply the gamma correction and brightness to a pixel
+    pub fn correct(&self, Color { r, g, b, w }: Color) -> Color {
+        Color::rgbw(
+            self.correct_channel(r),
+            self.correct_channel(g),
+            self.correct_channel(b),
+            self.correct_channel(w),
+        )
+    }
+
+    fn correct_channel(&self, value: u8) -> u8 {
+        let value = if self.gamma {
+            GAMMA8[value as usize]
+        } else {
+            value
+        };
+        ((value as u16 * (self.brightness as u16 + 1)) >> 8) as u8
+    }
+}
+
+/// A limit on the current drawn by the pixels, estimated from their channel
+/// values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PowerBudget {
+    /// The most current the pixels may draw, in mA
+    pub max_ma: u32,
+    /// The current drawn by one channel of one pixel at full brightness, in mA
+    pub channel_ma: u16,
+}
+
+impl PowerBudget {
+    /// A budget of `max_ma`, for pixels drawing the typical 20mA per channel
+    pub const fn new(max_ma: u32) -> Self {
+        Self {
+            max_ma,
+            channel_ma: 20,
+        }
+    }
+
+    /// The estimated current drawn by a frame of pixels in the given format, in
+    /// mA. Only the channels the format sends count, so the white channel is
+    /// ignored for RGB pixels.
+    pub fn estimate_ma(&self, format: PixelFormat, frame: impl Iterator<Item = Color>) -> u32 {
+        let channels = format.bytes_per_pixel() as usize;
+        let total: u32 = frame
+            .map(|color| {
+                format.encode(color)[..channels]
+                    .iter()
+                    .map(|&c| c as u32)
+                    .sum::<u32>()
+            })
+            .sum();
+        (total as u64 * self.channel_ma as u64 / 255) as u32
+    }
+
+    /// The level, out of 255, that a frame of pixels in the given format must
+    /// be scaled by to stay within the budget
+    pub fn level(&self, format: PixelFormat, frame: impl Iterator<Item = Color>) -> u8 {
+        match self.estimate_ma(format, frame) {
+            ma if ma <= self.max_ma => u8::MAX,
+            ma => (self.max_ma as u64 * 255 / ma as u64) as u8,
+        }
+    }
+}
+
+/// Gamma correction table for a gamma of 2.8
+pub const GAMMA8: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 5, 5, 5,
+    5, 6, 6, 6, 6, 7, 7, 7, 7, 8, 8, 8, 9, 9, 9, 10, 10, 10, 11, 11, 11, 12, 12, 13, 13, 13, 14,
+    14, 15, 15, 16, 16, 17, 17, 18, 18, 19, 19, 20, 20, 21, 21, 22, 22, 23, 24, 24, 25, 25, 26, 27,
+    27, 28, 29, 29, 30, 31, 32, 32, 33, 34, 35, 35, 36, 37, 38, 39, 39, 40, 41, 42, 43, 44, 45, 46,
+    47, 48, 49, 50, 50, 51, 52, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 66, 67, 68, 69, 70, 72,
+    73, 74, 75, 77, 78, 79, 81, 82, 83, 85, 86, 87, 89, 90, 92, 93, 95, 96, 98, 99, 101, 102, 104,
+    105, 107, 109, 110, 112, 114, 115, 117, 119, 120, 122, 124, 126, 127, 129, 131, 133, 135, 137,
+    138, 140, 142, 144, 146, 148, 150, 152, 154, 156, 158, 160, 162, 164, 167, 169, 171, 173, 175,
+    177, 180, 182, 184, 186, 189, 191, 193, 196, 198, 200, 203, 205, 208, 210, 213, 215, 218, 220,
+    223, 225, 228, 231, 233, 236, 239, 241, 244, 247, 249, 252, 255,
+];
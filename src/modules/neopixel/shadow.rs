@@ -0,0 +1,161 @@
+use super::{show_due, write_pixels, Color, MonotonicClock, NeopixelModule};
+use crate::{driver::Driver, SeesawError};
+use core::marker::PhantomData;
+
+/// A host-side copy of a device's pixel buffer, since the Seesaw's buffer
+/// can't be read back.
+///
+/// Pixels are set on the shadow buffer and `flush` uploads only the pixels that
+/// changed since the last flush, one run of contiguous changed pixels at a
+/// time, before showing them. If nothing changed, nothing is sent at all.
+///
+/// Every pixel starts out changed, so the first flush uploads the whole
+/// buffer.
+#[derive(Debug)]
+pub struct NeopixelShadow<D, T: NeopixelModule<D>>
+where
+    D: Driver,
+    [(); T::N_LEDS as usize]: Sized,
+{
+    device: T,
+    pixels: [Color; T::N_LEDS as usize],
+    dirty: [bool; T::N_LEDS as usize],
+    /// The brightness and gamma settings the device's buffer was uploaded
+    /// with
+    uploaded_with: Option<(u8, bool)>,
+    _driver: PhantomData<D>,
+}
+
+impl<D, T: NeopixelModule<D>> NeopixelShadow<D, T>
+where
+    D: Driver,
+    [(); T::N_LEDS as usize]: Sized,
+{
+    pub fn new(device: T) -> Self {
+        Self {
+            device,
+            pixels: [Color::BLACK; T::N_LEDS as usize],
+            dirty: [true; T::N_LEDS as usize],
+            uploaded_with: None,
+            _driver: PhantomData,
+        }
+    }
+
+    pub fn device(&mut self) -> &mut T {
+        &mut self.device
+    }
+
+    pub fn into_inner(self) -> T {
+        self.device
+    }
+
+    /// The color of a pixel as last set on the shadow buffer, or `None` if the
+    /// pixel is beyond the end of the strip
+    pub fn get(&self, n: u16) -> Option<Color> {
+        self.pixels.get(n as usize).copied()
+    }
+
+    /// Set the color of a pixel on the shadow buffer. Fails with
+    /// `InvalidPixel` if the pixel is beyond the end of the strip.
+    pub fn set(&mut self, n: u16, color: impl Into<Color>) -> Result<(), SeesawError<D::I2cError>> {
+        let pixel = self
+            .pixels
+            .get_mut(n as usize)
+            .ok_or(SeesawError::InvalidPixel(n))?;
+        let color = color.into();
+        if *pixel != color {
+            *pixel = color;
+            self.dirty[n as usize] = true;
+        }
+        Ok(())
+    }
+
+    pub fn fill(&mut self, color: impl Into<Color>) {
+        let color = color.into();
+        for (pixel, dirty) in self.pixels.iter_mut().zip(self.dirty.iter_mut()) {
+            if *pixel != color {
+                *pixel = color;
+                *dirty = true;
+            }
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.contains(&true)
+    }
+
+    /// Upload every pixel on the next flush, e.g. after the device has been
+    /// reset
+    pub fn invalidate(&mut self) {
+        self.dirty = [true; T::N_LEDS as usize];
+    }
+
+    /// Upload the changed pixels and show them. Returns `false` without
+    /// touching the bus if no pixel has changed.
+    ///
+    /// The whole buffer is measured against the device's power budget, and
+    /// every pixel is uploaded again whenever that, or a change to the device's
+    /// brightness or gamma settings, changes how the pixels are scaled.
+    pub fn flush(&mut self) -> Result<bool, SeesawError<D::I2cError>> {
+        if !self.upload()? {
+            return Ok(false);
+        }
+        self.device.sync_neopixel()?;
+        Ok(true)
+    }
+
+    /// Upload the changed pixels and show them like `flush`, but only once the
+    /// last frame shown with the clock has latched, and without waiting for
+    /// this one to. Returns `false` without touching the bus if no pixel has
+    /// changed or the last frame is still latching; the changes are kept for
+    /// the next flush.
+    pub fn try_flush(
+        &mut self,
+        clock: &mut impl MonotonicClock,
+    ) -> Result<bool, SeesawError<D::I2cError>> {
+        let now_us = clock.now_us();
+        if !show_due(&self.device, now_us) || !self.upload()? {
+            return Ok(false);
+        }
+        self.device.try_sync_neopixel(&mut || now_us)
+    }
+
+    /// Upload the changed pixels, returning whether there were any
+    fn upload(&mut self) -> Result<bool, SeesawError<D::I2cError>> {
+        let settings = self
+            .device
+            .neopixel_settings()
+            .limit(T::PIXEL_FORMAT, self.pixels.iter().copied());
+        let scaling = (settings.brightness(), settings.gamma());
+        if self.uploaded_with != Some(scaling) {
+            self.invalidate();
+        }
+        if !self.is_dirty() {
+            return Ok(false);
+        }
+
+        let addr = self.device.addr();
+        let mut n = 0;
+        while n < self.dirty.len() {
+            if !self.dirty[n] {
+                n += 1;
+                continue;
+            }
+            let run = self.dirty[n..].iter().take_while(|&&dirty| dirty).count();
+            write_pixels(
+                self.device.driver(),
+                addr,
+                T::PIXEL_FORMAT,
+                &settings,
+                n as u16,
+                self.pixels[n..n + run].iter().copied(),
+            )
+            .map_err(SeesawError::I2c)?;
+            n += run;
+        }
+
+        self.dirty = [false; T::N_LEDS as usize];
+        self.uploaded_with = Some(scaling);
+        Ok(true)
+    }
+}
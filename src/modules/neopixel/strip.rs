@@ -0,0 +1,180 @@
+use super::{
+    enable, frame_settings, set_speed, show, try_show, write_pixels, Color, MonotonicClock,
+    NeopixelSettings, NeopixelSpeed, PixelFormat,
+};
+use crate::{driver::Driver, SeesawDevice, SeesawError};
+
+/// A neopixel strip whose length is only known at runtime, e.g. one attached
+/// to a NeoDriver or a bare SAMD09 board.
+///
+/// Devices with onboard neopixels declare them with the `NeopixelModule`
+/// instead. The strip holds its configuration and its own runtime settings,
+/// which are applied to everything uploaded. The device is passed to each
+/// method, so it works with any device type. The power budget can only be
+/// applied to whole frames, so while one is set, uploading part of the strip
+/// with `set` or `set_colors` fails with `PartialFrame`.
+///
+/// ```ignore
+/// let mut strip = NeopixelStrip::new(15, 120, PixelFormat::Grb);
+/// strip.settings_mut().set_brightness(64);
+/// strip.enable(&mut device)?;
+/// strip.fill(&mut device, Color::BLUE.scale(32))?;
+/// strip.show(&mut device)?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NeopixelStrip {
+    pin: u8,
+    len: u16,
+    format: PixelFormat,
+    settings: NeopixelSettings,
+}
+
+impl NeopixelStrip {
+    pub fn new(pin: u8, len: u16, format: PixelFormat) -> Self {
+        Self {
+            pin,
+            len,
+            format,
+            settings: NeopixelSettings::default(),
+        }
+    }
+
+    pub const fn pin(&self) -> u8 {
+        self.pin
+    }
+
+    pub const fn len(&self) -> u16 {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn settings(&self) -> &NeopixelSettings {
+        &self.settings
+    }
+
+    pub fn settings_mut(&mut self) -> &mut NeopixelSettings {
+        &mut self.settings
+    }
+
+    /// Configure the device's neopixel output for this strip. Fails with
+    /// `InvalidPin` if the chip can't drive the pin, or `InvalidStripLength`
+    /// if the strip doesn't fit in the chip's neopixel buffer.
+    pub fn enable<D: Driver, T: SeesawDevice<Driver = D>>(
+        &self,
+        device: &mut T,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        let addr = device.addr();
+        enable(
+            device.driver(),
+            addr,
+            T::HARDWARE_ID,
+            self.pin,
+            self.len,
+            self.format,
+        )
+    }
+
+    pub fn set_speed<D: Driver, T: SeesawDevice<Driver = D>>(
+        &mut self,
+        device: &mut T,
+        speed: NeopixelSpeed,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        let addr = device.addr();
+        set_speed(device.driver(), addr, speed)?;
+        self.settings.set_speed(speed);
+        Ok(())
+    }
+
+    /// Set the color of a pixel. The white channel is ignored for RGB pixel
+    /// formats. Fails with `InvalidPixel` if the pixel is beyond the end of the
+    /// strip.
+    pub fn set<D: Driver, T: SeesawDevice<Driver = D>>(
+        &self,
+        device: &mut T,
+        n: u16,
+        color: impl Into<Color>,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        self.write(device, n, [color.into()].into_iter())
+    }
+
+    /// Set the colors of consecutive pixels, starting at pixel `start`. Fails
+    /// with `InvalidPixel` if any would be beyond the end of the strip.
+    pub fn set_colors<D: Driver, T: SeesawDevice<Driver = D>>(
+        &self,
+        device: &mut T,
+        start: u16,
+        colors: &[impl Into<Color> + Copy],
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        self.write(device, start, colors.iter().map(|&color| color.into()))
+    }
+
+    /// Set every pixel of the strip to the same color
+    pub fn fill<D: Driver, T: SeesawDevice<Driver = D>>(
+        &self,
+        device: &mut T,
+        color: impl Into<Color>,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        self.write(
+            device,
+            0,
+            core::iter::repeat_n(color.into(), self.len as usize),
+        )
+    }
+
+    /// Show the uploaded colors, then wait until the pixels have latched them
+    pub fn show<D: Driver, T: SeesawDevice<Driver = D>>(
+        &mut self,
+        device: &mut T,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        let addr = device.addr();
+        show(
+            device.driver(),
+            addr,
+            &mut self.settings,
+            self.len,
+            self.format,
+        )
+    }
+
+    /// Show the uploaded colors unless the last frame shown by this method is
+    /// still being latched, or was shown less than a frame ago at the max FPS.
+    /// Returns whether the colors were shown, without ever waiting.
+    pub fn try_show<D: Driver, T: SeesawDevice<Driver = D>>(
+        &mut self,
+        device: &mut T,
+        clock: &mut impl MonotonicClock,
+    ) -> Result<bool, SeesawError<D::I2cError>> {
+        let addr = device.addr();
+        try_show(
+            device.driver(),
+            addr,
+            &mut self.settings,
+            self.len,
+            self.format,
+            clock,
+        )
+    }
+
+    fn write<D: Driver, T: SeesawDevice<Driver = D>>(
+        &self,
+        device: &mut T,
+        start: u16,
+        pixels: impl ExactSizeIterator<Item = Color> + Clone,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        if start as usize + pixels.len() > self.len as usize {
+            return Err(SeesawError::InvalidPixel(start.max(self.len)));
+        }
+        let addr = device.addr();
+        let settings =
+            frame_settings(&self.settings, self.len, self.format, start, pixels.clone())?;
+        write_pixels(device.driver(), addr, self.format, &settings, start, pixels)
+            .map_err(SeesawError::I2c)
+    }
+}
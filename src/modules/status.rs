@@ -41,7 +41,16 @@ pub trait StatusModule<D: Driver>: SeesawDevice<Driver = D> {
             .map_err(crate::SeesawError::I2c)
     }
 
+    /// Reset the device, then verify both its hardware ID and product ID
     fn reset_and_verify_seesaw(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+        self.reset_and_verify_hardware()
+            .and_then(|_| self.verify_product_id())
+    }
+
+    /// Reset the device, then verify only its hardware ID. Boards running
+    /// custom firmware may report a product ID of their own, so this is the
+    /// check to use for them.
+    fn reset_and_verify_hardware(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
         let hw_id = Self::HARDWARE_ID;
         self.reset().and_then(|_| match self.hardware_id() {
             Ok(id) if id == hw_id.into() => Ok(()),
@@ -50,12 +59,34 @@ pub trait StatusModule<D: Driver>: SeesawDevice<Driver = D> {
         })
     }
 
+    /// Check that every module in `SeesawDevice::MODULES` is reported by the
+    /// device's capabilities
+    fn verify_modules(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+        let capabilities = self.capabilities()?;
+        match Self::MODULES
+            .iter()
+            .find(|&&module| !capabilities.has(module))
+        {
+            Some(&module) => Err(crate::SeesawError::MissingModule(module)),
+            None => Ok(()),
+        }
+    }
+
+    fn verify_product_id(&mut self) -> Result<(), crate::SeesawError<D::I2cError>> {
+        let product_id = Self::PRODUCT_ID;
+        match self.product_info() {
+            Ok(info) if info.id == product_id => Ok(()),
+            Ok(info) => Err(crate::SeesawError::InvalidProductId(info.id)),
+            Err(e) => Err(e),
+        }
+    }
+
     fn temp(&mut self) -> Result<f32, crate::SeesawError<D::I2cError>> {
         let addr = self.addr();
 
         self.driver()
             .read_u32(addr, STATUS_TEMP)
-            .map(|buf| (buf as f32 / (1u32 << 16) as f32))
+            .map(|buf| buf as f32 / (1u32 << 16) as f32)
             .map_err(crate::SeesawError::I2c)
     }
 }
@@ -79,6 +110,27 @@ pub struct DeviceCapabilities {
     pub touch: bool,
 }
 
+impl DeviceCapabilities {
+    pub fn has(&self, module: Modules) -> bool {
+        match module {
+            Modules::Adc => self.adc,
+            Modules::Dac => self.dac,
+            Modules::Dap => self.dap,
+            Modules::Eeprom => self.eeprom,
+            Modules::Encoder => self.encoder,
+            Modules::Gpio => self.gpio,
+            Modules::Interrupt => self.interrupt,
+            Modules::Keypad => self.keypad,
+            Modules::Neopixel => self.neopixel,
+            Modules::Sercom0 => self.sercom0,
+            Modules::Spectrum => self.spectrum,
+            Modules::Status => self.status,
+            Modules::Timer => self.timer,
+            Modules::Touch => self.touch,
+        }
+    }
+}
+
 impl From<u32> for DeviceCapabilities {
     fn from(value: u32) -> Self {
         DeviceCapabilities {
@@ -101,7 +153,7 @@ impl From<u32> for DeviceCapabilities {
 }
 
 /// StatusModule
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug)]
 pub struct ProductDateCode {
     pub id: u16,
     pub year: u16,
@@ -1,32 +1,83 @@
 use crate::{
     common::{Modules, Reg},
-    DriverExt, HardwareId,
+    DriverExt,
 };
 
-/// WO - 16 bits
+pub mod servo;
+pub mod tone;
+
+/// WO - 16 or 24 bits
 /// The first byte of the register indicates which PWM pin will have its value
-/// set The second byte is the actual PWM value
+/// set The second byte is the actual PWM value. If two value bytes are written,
+/// they're taken as a big-endian 16-bit value.
 const PWM_VAL: &Reg = &[Modules::Timer.into_u8(), 0x01];
 
-/// The PWM module provides up to 4 8-bit PWM outputs.
+/// WO - 24 bits
+/// The first byte of the register indicates which PWM pin will have its
+/// frequency set. The second and third bytes are the big-endian frequency in
+/// Hz.
+const FREQ: &Reg = &[Modules::Timer.into_u8(), 0x02];
+
+/// The PWM module provides up to 4 PWM outputs, with 8-bit or 16-bit duty
+/// cycles and a configurable frequency.
 /// The module base register address for the PWM module is 0x08.
-/// PWM outputs are available on pins PA04, PA05, PA06, and PA07.
+/// On the SAMD09, PWM outputs are available on pins PA04, PA05, PA06, and
+/// PA07. See `HardwareId::pins` for the PWM pins of the other chips.
 pub trait TimerModule<D: crate::Driver>: crate::SeesawDevice<Driver = D> {
     fn analog_write(&mut self, pin: u8, value: u8) -> Result<(), crate::SeesawError<D::I2cError>> {
-        let mapped_pin = match Self::HARDWARE_ID {
-            HardwareId::ATTINY817 => pin,
-            HardwareId::SAMD09 => match pin {
-                4 => 0,
-                5 => 1,
-                6 => 2,
-                7 => 3,
-                _ => 0,
-            },
-        };
+        let mapped_pin = Self::HARDWARE_ID
+            .pins()
+            .pwm_channel(pin)
+            .ok_or(crate::SeesawError::InvalidPin(pin))?;
 
         let addr = self.addr();
         self.driver()
             .write_u16(addr, PWM_VAL, u16::from_be_bytes([mapped_pin, value]))
             .map_err(crate::SeesawError::I2c)
     }
+
+    /// Set the duty cycle of a PWM pin with 16-bit resolution
+    fn analog_write_u16(
+        &mut self,
+        pin: u8,
+        value: u16,
+    ) -> Result<(), crate::SeesawError<D::I2cError>> {
+        write_pwm_register(self, PWM_VAL, pin, value)
+    }
+
+    /// Set the PWM frequency of a pin in Hz. On chips where several pins share
+    /// a timer, this changes the frequency of all of them.
+    fn set_pwm_freq(&mut self, pin: u8, hz: u16) -> Result<(), crate::SeesawError<D::I2cError>> {
+        write_pwm_register(self, FREQ, pin, hz)
+    }
+
+    /// Play a square wave of the given frequency at 50% duty, e.g. on a piezo
+    /// buzzer, until `no_tone` is called
+    fn tone(&mut self, pin: u8, hz: u16) -> Result<(), crate::SeesawError<D::I2cError>> {
+        self.set_pwm_freq(pin, hz)
+            .and_then(|_| self.analog_write_u16(pin, u16::MAX / 2 + 1))
+    }
+
+    fn no_tone(&mut self, pin: u8) -> Result<(), crate::SeesawError<D::I2cError>> {
+        self.analog_write_u16(pin, 0)
+    }
+}
+
+fn write_pwm_register<D: crate::Driver, T: TimerModule<D> + ?Sized>(
+    device: &mut T,
+    reg: &Reg,
+    pin: u8,
+    value: u16,
+) -> Result<(), crate::SeesawError<D::I2cError>> {
+    let mapped_pin = T::HARDWARE_ID
+        .pins()
+        .pwm_channel(pin)
+        .ok_or(crate::SeesawError::InvalidPin(pin))?;
+    let [hi, lo] = value.to_be_bytes();
+
+    let addr = device.addr();
+    device
+        .driver()
+        .register_write(addr, reg, &[mapped_pin, hi, lo])
+        .map_err(crate::SeesawError::I2c)
 }
@@ -0,0 +1,114 @@
+use super::TimerModule;
+use crate::{driver::Driver, SeesawError};
+
+/// Servos expect a pulse every 20ms
+const SERVO_FREQ_HZ: u16 = 50;
+const PERIOD_US: u32 = 1_000_000 / SERVO_FREQ_HZ as u32;
+
+/// A hobby servo driven from a PWM pin of any device that implements
+/// `TimerModule`.
+///
+/// The servo only holds its pin and calibration, so several servos can share
+/// a device, which is passed to each call.
+#[derive(Clone, Copy, Debug)]
+pub struct Servo {
+    pin: u8,
+    min_pulse_us: u16,
+    max_pulse_us: u16,
+    max_angle: u16,
+}
+
+impl Servo {
+    /// Create a servo on a PWM pin, with the common 1000-2000µs pulse range
+    /// over 180 degrees
+    pub const fn new(pin: u8) -> Self {
+        Self {
+            pin,
+            min_pulse_us: 1000,
+            max_pulse_us: 2000,
+            max_angle: 180,
+        }
+    }
+
+    /// Calibrate the pulse widths for the ends of the servo's travel
+    pub const fn with_pulse_range(self, min_pulse_us: u16, max_pulse_us: u16) -> Self {
+        assert!(min_pulse_us < max_pulse_us);
+        Self {
+            min_pulse_us,
+            max_pulse_us,
+            ..self
+        }
+    }
+
+    /// Set the angle covered by the pulse range, for servos that travel more
+    /// or less than 180 degrees
+    pub const fn with_max_angle(self, max_angle: u16) -> Self {
+        assert!(max_angle > 0);
+        Self { max_angle, ..self }
+    }
+
+    pub const fn pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// Set the pin's PWM frequency to 50Hz. This has to be called before the
+    /// servo is moved.
+    pub fn enable<D: Driver, T: TimerModule<D>>(
+        &self,
+        device: &mut T,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        device.set_pwm_freq(self.pin, SERVO_FREQ_HZ)
+    }
+
+    /// Stop sending pulses, which lets the servo go limp
+    pub fn disable<D: Driver, T: TimerModule<D>>(
+        &self,
+        device: &mut T,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        device.analog_write_u16(self.pin, 0)
+    }
+
+    /// Move to an angle in degrees, clamped to the servo's range
+    pub fn set_angle<D: Driver, T: TimerModule<D>>(
+        &self,
+        device: &mut T,
+        degrees: u16,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        let degrees = degrees.min(self.max_angle) as u32;
+        let range = (self.max_pulse_us - self.min_pulse_us) as u32;
+        let pulse_us = self.min_pulse_us as u32 + range * degrees / self.max_angle as u32;
+        self.write_pulse(device, pulse_us)
+    }
+
+    /// Drive a continuous rotation servo at a speed from -100 (full speed
+    /// reverse) to 100 (full speed forward), where 0 stops it
+    pub fn set_speed<D: Driver, T: TimerModule<D>>(
+        &self,
+        device: &mut T,
+        speed: i8,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        let speed = speed.clamp(-100, 100) as i32;
+        let center = (self.min_pulse_us as i32 + self.max_pulse_us as i32) / 2;
+        let half_range = (self.max_pulse_us as i32 - self.min_pulse_us as i32) / 2;
+        self.write_pulse(device, (center + half_range * speed / 100) as u32)
+    }
+
+    /// Send a pulse of the given width, clamped to the calibrated range
+    pub fn set_pulse_width<D: Driver, T: TimerModule<D>>(
+        &self,
+        device: &mut T,
+        pulse_us: u16,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        let pulse_us = pulse_us.clamp(self.min_pulse_us, self.max_pulse_us);
+        self.write_pulse(device, pulse_us as u32)
+    }
+
+    fn write_pulse<D: Driver, T: TimerModule<D>>(
+        &self,
+        device: &mut T,
+        pulse_us: u32,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        let duty = (pulse_us.min(PERIOD_US) * u16::MAX as u32 / PERIOD_US) as u16;
+        device.analog_write_u16(self.pin, duty)
+    }
+}
@@ -0,0 +1,124 @@
+use super::TimerModule;
+use crate::{driver::Driver, SeesawError};
+
+/// Note frequencies in Hz, from C4 (middle C) to B5
+pub mod notes {
+    pub const REST: u16 = 0;
+    pub const C4: u16 = 262;
+    pub const CS4: u16 = 277;
+    pub const D4: u16 = 294;
+    pub const DS4: u16 = 311;
+    pub const E4: u16 = 330;
+    pub const F4: u16 = 349;
+    pub const FS4: u16 = 370;
+    pub const G4: u16 = 392;
+    pub const GS4: u16 = 415;
+    pub const A4: u16 = 440;
+    pub const AS4: u16 = 466;
+    pub const B4: u16 = 494;
+    pub const C5: u16 = 523;
+    pub const CS5: u16 = 554;
+    pub const D5: u16 = 587;
+    pub const DS5: u16 = 622;
+    pub const E5: u16 = 659;
+    pub const F5: u16 = 698;
+    pub const FS5: u16 = 740;
+    pub const G5: u16 = 784;
+    pub const GS5: u16 = 831;
+    pub const A5: u16 = 880;
+    pub const AS5: u16 = 932;
+    pub const B5: u16 = 988;
+}
+
+/// Plays a sequence of `(frequency in Hz, duration in ms)` notes on a PWM pin
+/// without blocking. A frequency of 0 is a rest.
+///
+/// Call `tick` regularly with the current time in milliseconds; the next note
+/// is started once the current one has played for its duration.
+#[derive(Clone, Debug)]
+pub struct Melody<'a> {
+    pin: u8,
+    notes: &'a [(u16, u32)],
+    index: usize,
+    note_started_ms: Option<u32>,
+    looping: bool,
+}
+
+impl<'a> Melody<'a> {
+    pub const fn new(pin: u8, notes: &'a [(u16, u32)]) -> Self {
+        Self {
+            pin,
+            notes,
+            index: 0,
+            note_started_ms: None,
+            looping: false,
+        }
+    }
+
+    /// Start again from the first note once the melody has finished
+    pub const fn looping(self) -> Self {
+        Self {
+            looping: true,
+            ..self
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.index >= self.notes.len()
+    }
+
+    /// Rewind to the first note. The next `tick` starts playing it.
+    pub fn restart(&mut self) {
+        self.index = 0;
+        self.note_started_ms = None;
+    }
+
+    /// Silence the pin and skip to the end of the melody
+    pub fn stop<D: Driver, T: TimerModule<D>>(
+        &mut self,
+        device: &mut T,
+    ) -> Result<(), SeesawError<D::I2cError>> {
+        self.index = self.notes.len();
+        self.note_started_ms = None;
+        device.no_tone(self.pin)
+    }
+
+    /// Advance the melody to `now_ms`. Returns `false` once the melody has
+    /// finished.
+    pub fn tick<D: Driver, T: TimerModule<D>>(
+        &mut self,
+        device: &mut T,
+        now_ms: u32,
+    ) -> Result<bool, SeesawError<D::I2cError>> {
+        loop {
+            if self.is_finished() {
+                if self.looping && self.notes.iter().any(|&(_, duration)| duration > 0) {
+                    self.restart();
+                } else {
+                    return Ok(false);
+                }
+            }
+
+            let (hz, duration_ms) = self.notes[self.index];
+            match self.note_started_ms {
+                None => {
+                    self.note_started_ms = Some(now_ms);
+                    match hz {
+                        0 => device.no_tone(self.pin)?,
+                        hz => device.tone(self.pin, hz)?,
+                    }
+                }
+                Some(started) if now_ms.wrapping_sub(started) >= duration_ms => {
+                    self.index += 1;
+                    self.note_started_ms = None;
+                    if self.is_finished() && !self.looping {
+                        device.no_tone(self.pin)?;
+                    }
+                    continue;
+                }
+                Some(_) => {}
+            }
+            return Ok(true);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    devices::{DeviceKind, GenericDevice},
+    driver::Driver,
+    modules::status::{DeviceCapabilities, ProductDateCode, StatusModule},
+    HardwareId, SeesawDevice,
+};
+
+use core::{iter::Chain, ops::RangeInclusive};
+
+/// The non-reserved 7-bit I2C addresses, which `Seesaw::scan` probes
+pub const VALID_ADDRS: RangeInclusive<u8> = 0x08..=0x77;
+
+/// The addresses the known devices can be jumpered to. 0x50, which the
+/// QuadRotaryEncoder reaches with all its address jumpers cut, is left out
+/// because it's the usual address of I2C EEPROMs.
+pub const KNOWN_ADDRS: [RangeInclusive<u8>; 2] = [0x30..=0x3F, 0x49..=0x4F];
+
+/// The addresses `Seesaw::scan_known_addrs` probes, see `KNOWN_ADDRS`
+pub type KnownAddrs = Chain<RangeInclusive<u8>, RangeInclusive<u8>>;
+
+/// A Seesaw device that responded during a bus scan
+#[derive(Copy, Clone, Debug)]
+pub struct DetectedDevice {
+    pub addr: u8,
+    pub hardware_id: HardwareId,
+    pub product: ProductDateCode,
+    pub capabilities: DeviceCapabilities,
+    /// The matching device type, or `None` if the product ID isn't one this
+    /// library knows about
+    pub kind: Option<DeviceKind>,
+}
+
+/// Iterator over the Seesaw devices on a bus, created by `Seesaw::scan`,
+/// `Seesaw::scan_known_addrs` or `Seesaw::scan_addrs`.
+///
+/// Each address is probed by reading its `STATUS_HW_ID` register. Addresses
+/// that don't respond, or that respond with a hardware ID that doesn't belong
+/// to a Seesaw chip, are skipped. For the rest, the `STATUS_VERSION` and
+/// `STATUS_OPTIONS` registers are read to identify the product and its
+/// capabilities. Reserved addresses are never probed.
+///
+/// Reading a Seesaw register starts with writing the register's address, so
+/// every probed device gets the bytes `[0x00, 0x01]` written to it. Many
+/// non-Seesaw parts treat that as a register or data write, e.g. an EEPROM
+/// stores 0x01 at address 0. Only scan addresses where no such part can be.
+#[derive(Debug)]
+pub struct Scan<D, A = RangeInclusive<u8>> {
+    driver: D,
+    addrs: A,
+}
+
+impl<D: Driver + Clone> Scan<D> {
+    /// Scan every non-reserved address, see `VALID_ADDRS`
+    pub fn new(driver: D) -> Self {
+        Self::with_addrs(driver, VALID_ADDRS)
+    }
+}
+
+impl<D: Driver + Clone> Scan<D, KnownAddrs> {
+    /// Scan only the addresses the known devices can use, see `KNOWN_ADDRS`
+    pub fn known(driver: D) -> Self {
+        let [low, high] = KNOWN_ADDRS;
+        Self::with_addrs(driver, low.chain(high))
+    }
+}
+
+impl<D: Driver + Clone, A: Iterator<Item = u8>> Scan<D, A> {
+    pub fn with_addrs(driver: D, addrs: impl IntoIterator<IntoIter = A>) -> Self {
+        Self {
+            driver,
+            addrs: addrs.into_iter(),
+        }
+    }
+}
+
+impl<D: Driver + Clone, A: Iterator<Item = u8>> Iterator for Scan<D, A> {
+    type Item = DetectedDevice;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let driver = &self.driver;
+        self.addrs
+            .by_ref()
+            .filter(|addr| VALID_ADDRS.contains(addr))
+            .find_map(|addr| probe(driver, addr))
+    }
+}
+
+fn probe<D: Driver + Clone>(driver: &D, addr: u8) -> Option<DetectedDevice> {
+    let mut device = GenericDevice::new(addr, driver.clone());
+    let hardware_id = HardwareId::try_from(device.hardware_id().ok()?).ok()?;
+    let product = device.product_info().ok()?;
+    let capabilities = device.capabilities().ok()?;
+
+    Some(DetectedDevice {
+        addr,
+        hardware_id,
+        product,
+        capabilities,
+        kind: DeviceKind::from_product_id(product.id),
+    })
+}